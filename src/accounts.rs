@@ -0,0 +1,1005 @@
+use std::{
+    fmt::Display,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::{mpsc::UnboundedSender, Mutex};
+use zeroize::Zeroize;
+
+use crate::{
+    config::{AddAccountProvider, Configuration, SupersededDeviceAuth},
+    egl::{EpicGamesLauncher, RememberMeEntry, EPIC_GAMES_LAUNCHER_PROCESS},
+    epic::{
+        self, error_codes, AccountDescriptor, DeviceAuth, DeviceAuthInfo, DeviceAuthorization, EpicAccount, EpicError, EpicErrorKind,
+        Token,
+    },
+    epic_clients::AuthClient,
+    game::{self, GameInstances},
+    get_client,
+    health::AccountHealth,
+    launchers::{wait_for_exit, Launcher, LauncherSession, Launchers},
+    loopback::{LoopbackListener, LOGIN_TIMEOUT},
+    secret_store,
+};
+
+//account workflows shared by the gui, the cli and the tests, nothing in here knows about egui
+
+#[derive(Debug, Clone)]
+pub enum AccountOutcome {
+    Added(AccountDescriptor),
+    Linked(AccountDescriptor, Launchers),
+    Swapped(AccountDescriptor, Launchers),
+    SettingsCloned { from: String, to: String },
+    Removed(AccountDescriptor),
+    //the device auth was not deleted on epic
+    RemovedLocally(AccountDescriptor),
+    LauncherKilled,
+    GameStarted(AccountDescriptor, u32),
+    GameStopped(AccountDescriptor),
+    LaunchArgumentsSaved(AccountDescriptor),
+    ProfilesRefreshed { changed: usize, failed: usize },
+    HealthChecked { healthy: usize, broken: usize, unreachable: usize },
+    CorrectiveActionCompleted(AccountDescriptor),
+    LoggedOutEverywhere(AccountDescriptor),
+    DeviceAuthDeleted(AccountDescriptor),
+    SupersededDeviceAuthsDeleted { deleted: usize, failed: usize },
+}
+
+impl Display for AccountOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccountOutcome::Added(account) => write!(f, "Linked {} succesfully.", account.display_name),
+            AccountOutcome::Linked(account, launcher) => write!(
+                f,
+                "Linked {} succesfully, you may have to reconnect on {}.",
+                account.display_name, launcher
+            ),
+            AccountOutcome::Swapped(account, Launchers::EpicGamesLauncher) => {
+                write!(f, "Logged in as {} !", account.display_name)
+            }
+            AccountOutcome::Swapped(account, launcher) => {
+                write!(f, "Logged in as {} on {} !", account.display_name, launcher)
+            }
+            AccountOutcome::SettingsCloned { from, to } => {
+                write!(f, "The configuration of {} has been applied to {} successfully", from, to)
+            }
+            AccountOutcome::Removed(account) => write!(f, "Removed {} successfully", account.display_name),
+            AccountOutcome::RemovedLocally(account) => {
+                write!(f, "Removed {} without deleting its device auth on Epic", account.display_name)
+            }
+            AccountOutcome::LauncherKilled => write!(f, "Killed EpicGamesLauncher successfully"),
+            AccountOutcome::GameStarted(account, pid) => {
+                write!(f, "Fortnite is starting as {} (process {}) !", account.display_name, pid)
+            }
+            AccountOutcome::GameStopped(account) => write!(f, "Stopped the game of {}", account.display_name),
+            AccountOutcome::LaunchArgumentsSaved(account) => {
+                write!(f, "Saved the launch arguments of {}", account.display_name)
+            }
+            AccountOutcome::ProfilesRefreshed { changed, failed: 0 } => {
+                write!(f, "Refreshed the profiles, {} changed", changed)
+            }
+            AccountOutcome::ProfilesRefreshed { changed, failed } => {
+                write!(f, "Refreshed the profiles, {} changed and {} failed to log in", changed, failed)
+            }
+            AccountOutcome::CorrectiveActionCompleted(account) => {
+                write!(f, "{} can log in again", account.display_name)
+            }
+            AccountOutcome::LoggedOutEverywhere(account) => {
+                write!(f, "Logged {} out of every launcher, game and device", account.display_name)
+            }
+            AccountOutcome::DeviceAuthDeleted(account) => {
+                write!(f, "Deleted a device auth of {}", account.display_name)
+            }
+            AccountOutcome::SupersededDeviceAuthsDeleted { deleted, failed: 0 } => {
+                write!(f, "Deleted {} replaced device auths", deleted)
+            }
+            AccountOutcome::SupersededDeviceAuthsDeleted { deleted, failed } => {
+                write!(f, "Deleted {} replaced device auths, {} could not be deleted", deleted, failed)
+            }
+            AccountOutcome::HealthChecked { healthy, broken: 0, unreachable: 0 } => {
+                write!(f, "All {} accounts work", healthy)
+            }
+            AccountOutcome::HealthChecked { healthy, broken, unreachable } => write!(
+                f,
+                "{} accounts work, {} have to be linked again and {} could not be checked",
+                healthy, broken, unreachable
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovalMode {
+    //deletes the device auth on epic first, the account stays if epic can't be reached
+    RevokeOnEpic,
+    LocalOnly,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountProgress {
+    LoggingIn(String),
+    ClosingLauncher(Launchers),
+    WaitingForLauncherExit(Launchers),
+    WritingLauncherSession,
+    VerifyingLauncherSession,
+    StartingLauncher(Launchers),
+    RequestingExchangeCode,
+    StartingGame(String),
+    StoppingGame(String),
+    RefreshingProfile(String),
+    CheckingAccount(String),
+    CompletingCorrectiveAction(String),
+    LoggingOutEverywhere(String),
+    ListingDeviceAuths(String),
+    DeletingDeviceAuth(String),
+    WaitingForDeviceCode,
+    WaitingForBrowserLogin,
+    CreatingDeviceAuth,
+    AcceptingEula(String),
+    DownloadingSettings(String),
+    UploadingSettings(String),
+}
+
+impl Display for AccountProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccountProgress::LoggingIn(account) => write!(f, "Logging in as {}", account),
+            AccountProgress::ClosingLauncher(launcher) => write!(f, "Closing {}", launcher),
+            AccountProgress::WaitingForLauncherExit(launcher) => write!(f, "Waiting for {} to exit", launcher),
+            AccountProgress::WritingLauncherSession => write!(f, "Writing the launcher session"),
+            AccountProgress::VerifyingLauncherSession => write!(f, "Verifying the launcher session"),
+            AccountProgress::StartingLauncher(launcher) => write!(f, "Starting {}", launcher),
+            AccountProgress::RequestingExchangeCode => write!(f, "Requesting an exchange code"),
+            AccountProgress::StartingGame(account) => write!(f, "Starting Fortnite as {}", account),
+            AccountProgress::StoppingGame(account) => write!(f, "Stopping the game of {}", account),
+            AccountProgress::RefreshingProfile(account) => write!(f, "Refreshing the profile of {}", account),
+            AccountProgress::CheckingAccount(account) => write!(f, "Checking {}", account),
+            AccountProgress::CompletingCorrectiveAction(account) => {
+                write!(f, "Completing the action required by Epic on {}", account)
+            }
+            AccountProgress::LoggingOutEverywhere(account) => write!(f, "Logging {} out everywhere", account),
+            AccountProgress::ListingDeviceAuths(account) => write!(f, "Listing the device auths of {}", account),
+            AccountProgress::DeletingDeviceAuth(account) => write!(f, "Deleting a device auth of {}", account),
+            AccountProgress::WaitingForDeviceCode => write!(f, "Waiting for the device code to be approved"),
+            AccountProgress::WaitingForBrowserLogin => write!(f, "Waiting for the login in the browser"),
+            AccountProgress::CreatingDeviceAuth => write!(f, "Creating a device auth"),
+            AccountProgress::AcceptingEula(account) => write!(f, "Accepting the EULA of {}", account),
+            AccountProgress::DownloadingSettings(account) => write!(f, "Downloading the settings of {}", account),
+            AccountProgress::UploadingSettings(account) => write!(f, "Uploading the settings to {}", account),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AccountEvent {
+    Progress(AccountProgress),
+    DeviceCode(DeviceAuthorization),
+    AccountsChanged(Vec<AccountLabel>),
+    CurrentAccountChanged(Option<String>),
+    //account ids with a running game instance
+    GameInstancesChanged(Vec<String>),
+    //how many device auths wait to be deleted on epic
+    SupersededDeviceAuthsChanged(usize),
+}
+
+//accounts are keyed by their id, the display name is only shown
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccountLabel {
+    pub account_id: String,
+    pub display_name: String,
+    pub health: AccountHealth,
+}
+
+pub fn account_labels(configuration: &Configuration) -> Vec<AccountLabel> {
+    configuration
+        .accounts
+        .iter()
+        .filter_map(|account| {
+            Some(AccountLabel {
+                account_id: account.account_id()?.to_string(),
+                display_name: account.display_name.clone(),
+                health: account.health,
+            })
+        })
+        .collect()
+}
+
+pub type AccountEventSender = UnboundedSender<AccountEvent>;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExportedAccount {
+    pub display_name: String,
+    #[serde(rename = "accountId")]
+    pub account_id: String,
+    #[serde(rename = "deviceId")]
+    pub device_id: String,
+    pub secret: String,
+}
+
+impl Drop for ExportedAccount {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+#[derive(Clone)]
+pub struct AccountService {
+    configuration: Arc<Mutex<Configuration>>,
+    events: Option<AccountEventSender>,
+    game_instances: GameInstances,
+}
+
+fn not_found(account_id: &str) -> EpicError {
+    EpicError::new(
+        EpicErrorKind::NotFound,
+        Some(format!("Failed to find account {}", account_id)),
+    )
+}
+
+fn find_account<'a>(configuration: &'a Configuration, account_id: &str) -> Result<&'a AccountDescriptor, EpicError> {
+    configuration
+        .accounts
+        .iter()
+        .find(|x| x.account_id() == Some(account_id))
+        .ok_or(not_found(account_id))
+}
+
+//the cli takes an account id or a display name, a display name has to be unique
+pub fn resolve_account(configuration: &Configuration, account: &str) -> Result<String, EpicError> {
+    if find_account(configuration, account).is_ok() {
+        return Ok(account.to_string());
+    }
+
+    let account_ids: Vec<&str> = configuration
+        .accounts
+        .iter()
+        .filter(|x| x.display_name == account)
+        .filter_map(|x| x.account_id())
+        .collect();
+
+    match account_ids.as_slice() {
+        [account_id] => Ok(account_id.to_string()),
+        [] => Err(not_found(account)),
+        _ => Err(EpicError::new(
+            EpicErrorKind::Other,
+            Some(format!("Several accounts are named {}, use an account id", account)),
+        )),
+    }
+}
+
+fn find_account_mut<'a>(configuration: &'a mut Configuration, account_id: &str) -> Result<&'a mut AccountDescriptor, EpicError> {
+    configuration
+        .accounts
+        .iter_mut()
+        .find(|x| x.account_id() == Some(account_id))
+        .ok_or(not_found(account_id))
+}
+
+fn device_auth_of(account: &AccountDescriptor) -> Result<DeviceAuth, EpicError> {
+    account.device_auth.clone().ok_or(EpicError::new(
+        EpicErrorKind::Other,
+        Some("Account has no device_auth"),
+    ))
+}
+
+//logs in with the device auth of the account and deletes one of its device auths on epic
+async fn delete_device_auth_as(account: &AccountDescriptor, device_id: &str) -> Result<(), EpicError> {
+    let session = device_auth_of(account)?.login().await?;
+    let deleted = session.delete_device_auth(device_id).await;
+    let _ = session.kill_session().await;
+
+    deleted
+}
+
+//deletes the device auth the account logs in with
+async fn revoke_device_auth(account: &AccountDescriptor) -> Result<(), EpicError> {
+    delete_device_auth_as(account, &device_auth_of(account)?.device_id).await
+}
+
+//the account the selected launcher is currently logged in with
+pub fn current_launcher_account(configuration: &Configuration) -> Result<RememberMeEntry, EpicError> {
+    configuration.launcher.resolve(configuration)?.read_active_account()
+}
+
+pub async fn request_device_code() -> Result<DeviceAuthorization, EpicError> {
+    let client_token = epic::token(Token::ClientCredentials, get_client!("fortniteNewSwitchGameClient")).await?;
+    let authorization = client_token.get_device_authorization().await;
+    let _ = client_token.kill_session().await;
+
+    authorization
+}
+
+//polls until the user approved the code on epicgames.com/activate or the code expired
+pub async fn wait_for_device_code(authorization: &DeviceAuthorization) -> Result<EpicAccount, EpicError> {
+    let expires_at = Instant::now() + Duration::from_secs(authorization.expires_in.max(0) as u64);
+    let interval = Duration::from_secs(authorization.interval.max(1) as u64);
+
+    loop {
+        tokio::time::sleep(interval).await;
+
+        match epic::token(Token::DeviceCode(&authorization.device_code), get_client!("fortniteNewSwitchGameClient")).await {
+            Ok(account) => return Ok(account),
+            Err(error) => {
+                if Instant::now() >= expires_at {
+                    return Err(EpicError::new(
+                        EpicErrorKind::Authentification,
+                        Some(format!("The device code expired : {}", error)),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+impl AccountService {
+    pub fn new(configuration: Arc<Mutex<Configuration>>) -> Self {
+        Self {
+            configuration,
+            events: None,
+            game_instances: GameInstances::default(),
+        }
+    }
+
+    pub fn with_events(mut self, events: AccountEventSender) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn configuration(&self) -> Arc<Mutex<Configuration>> {
+        Arc::clone(&self.configuration)
+    }
+
+    //polls the game instances while someone listens to the events
+    pub fn watch_game_instances(&self, interval: Duration) {
+        let Some(events) = self.events.clone() else {
+            return;
+        };
+        let game_instances = self.game_instances.clone();
+
+        tokio::spawn(async move {
+            let mut in_game = game_instances.in_game();
+
+            while !events.is_closed() {
+                tokio::time::sleep(interval).await;
+
+                let instances = game_instances.clone();
+                let _ = tokio::task::spawn_blocking(move || instances.refresh()).await;
+
+                let current = game_instances.in_game();
+                if current != in_game {
+                    in_game = current;
+                    let _ = events.send(AccountEvent::GameInstancesChanged(in_game.clone()));
+                }
+            }
+        });
+    }
+
+    //refreshes the profiles and the health of the accounts right away, then once per interval
+    pub fn watch_profiles(&self, interval: Duration) {
+        let Some(events) = self.events.clone() else {
+            return;
+        };
+        let account_service = self.clone();
+
+        tokio::spawn(async move {
+            while !events.is_closed() {
+                if let Err(error) = account_service.refresh_profiles().await {
+                    eprintln!("Failed to refresh the profiles : {}", error);
+                }
+
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+
+    fn emit(&self, event: AccountEvent) {
+        if let Some(events) = &self.events {
+            let _ = events.send(event);
+        }
+    }
+
+    fn progress(&self, progress: AccountProgress) {
+        self.emit(AccountEvent::Progress(progress));
+    }
+
+    fn accounts_changed(&self, configuration: &Configuration) {
+        self.emit(AccountEvent::AccountsChanged(account_labels(configuration)));
+    }
+
+    pub async fn add_account<'a>(&self, token: Token<'a>, client: AuthClient<'a>) -> Result<AccountOutcome, EpicError> {
+        let account = epic::token(token, client).await?;
+        let outcome = self.add_epic_account(&account).await;
+        let _ = account.kill_session().await;
+
+        outcome
+    }
+
+    pub async fn add_epic_account(&self, account: &EpicAccount) -> Result<AccountOutcome, EpicError> {
+        let mut configuration = self.configuration.lock().await;
+
+        self.progress(AccountProgress::CreatingDeviceAuth);
+        let (descriptor, superseded) = configuration
+            .add_account(AddAccountProvider::EpicAccount(account))
+            .await
+            .map_err(|error| {
+                EpicError::new(
+                    EpicErrorKind::Other,
+                    Some(format!("Add Account failed with error : {}", error)),
+                )
+            })?;
+
+        let flushed = configuration.flush().is_ok();
+        self.accounts_changed(&configuration);
+        drop(configuration);
+
+        self.delete_replaced_device_auth(&descriptor, superseded, flushed).await;
+
+        Ok(AccountOutcome::Added(descriptor))
+    }
+
+    //a saved configuration which still refers to the replaced device auth would no longer log in
+    async fn delete_replaced_device_auth(&self, descriptor: &AccountDescriptor, device_id: Option<String>, flushed: bool) {
+        let Some(device_id) = device_id else {
+            return;
+        };
+
+        if !flushed {
+            eprintln!("Failed to save the configuration, the previous device auth of {} is kept", descriptor.display_name);
+            return;
+        }
+
+        self.progress(AccountProgress::DeletingDeviceAuth(descriptor.display_name.clone()));
+        if let Err(error) = delete_device_auth_as(descriptor, &device_id).await {
+            eprintln!("Failed to delete the previous device auth : {}", error);
+        }
+    }
+
+    pub async fn add_with_device_code(&self) -> Result<AccountOutcome, EpicError> {
+        let authorization = request_device_code().await?;
+        self.emit(AccountEvent::DeviceCode(authorization.clone()));
+
+        self.progress(AccountProgress::WaitingForDeviceCode);
+        let account = wait_for_device_code(&authorization).await?;
+        let outcome = self.add_epic_account(&account).await;
+        let _ = account.kill_session().await;
+
+        outcome
+    }
+
+    //the caller shows the local url of the listener to the user, the code has to be redeemed by the same client
+    pub async fn add_with_browser(&self, listener: &LoopbackListener, client: AuthClient<'_>) -> Result<AccountOutcome, EpicError> {
+        self.progress(AccountProgress::WaitingForBrowserLogin);
+        let code = tokio::time::timeout(LOGIN_TIMEOUT, listener.wait_for_code())
+            .await
+            .map_err(|_| EpicError::new(EpicErrorKind::Other, Some("The browser login timed out")))??;
+
+        self.add_account(Token::AuthorizationCode(&code), client).await
+    }
+
+    pub async fn current_account(&self) -> Result<RememberMeEntry, EpicError> {
+        current_launcher_account(&*self.configuration.lock().await)
+    }
+
+    //tells the listeners which account the selected launcher uses, none if it can't be read
+    pub async fn refresh_current_account(&self) {
+        let current_account = self.current_account().await.ok().map(|x| x.display_name);
+
+        self.emit(AccountEvent::CurrentAccountChanged(current_account));
+    }
+
+    pub async fn link_current_account(&self) -> Result<AccountOutcome, EpicError> {
+        let mut configuration = self.configuration.lock().await;
+        let data = current_launcher_account(&configuration)?;
+
+        self.progress(AccountProgress::CreatingDeviceAuth);
+        let (descriptor, superseded) = configuration
+            .add_account(AddAccountProvider::RememberMeEntry(&data))
+            .await
+            .map_err(|_| {
+                EpicError::new(
+                    EpicErrorKind::Other,
+                    Some("Failed to add account to configuration"),
+                )
+            })?;
+
+        let flushed = configuration.flush().is_ok();
+        self.accounts_changed(&configuration);
+        let launcher = configuration.launcher.clone();
+        drop(configuration);
+
+        self.delete_replaced_device_auth(&descriptor, superseded, flushed).await;
+
+        Ok(AccountOutcome::Linked(descriptor, launcher))
+    }
+
+    pub async fn resolve_account(&self, account: &str) -> Result<String, EpicError> {
+        resolve_account(&*self.configuration.lock().await, account)
+    }
+
+    pub async fn clone_settings(&self, clone_from_id: &str, clone_to_id: &str) -> Result<AccountOutcome, EpicError> {
+        let configuration = self.configuration.lock().await;
+
+        let clone_from = find_account(&configuration, clone_from_id)?;
+        let clone_to = find_account(&configuration, clone_to_id)?;
+        let clone_from_username = clone_from.display_name.as_str();
+        let clone_to_username = clone_to.display_name.as_str();
+
+        let mut clone_from_device_auth = device_auth_of(clone_from)?;
+        let mut clone_to_device_auth = device_auth_of(clone_to)?;
+
+        self.progress(AccountProgress::LoggingIn(clone_from_username.to_string()));
+        let clone_from_account = clone_from_device_auth.login().await?;
+        self.progress(AccountProgress::LoggingIn(clone_to_username.to_string()));
+        let clone_to_account = match clone_to_device_auth.login().await {
+            Ok(account) => account,
+            Err(error) => {
+                let _ = clone_from_account.kill_session().await;
+                return Err(error);
+            }
+        };
+
+        let copied = async {
+            self.progress(AccountProgress::AcceptingEula(clone_to_username.to_string()));
+            let _ = clone_to_account.accept_eula().await;
+            let _ = clone_to_account.grant_access().await;
+
+            self.progress(AccountProgress::DownloadingSettings(clone_from_username.to_string()));
+            let client_settings = clone_from_account
+                .get_user_file_content("ClientSettings.Sav")
+                .await?;
+
+            self.progress(AccountProgress::UploadingSettings(clone_to_username.to_string()));
+            clone_to_account
+                .insert_or_edit("ClientSettings.Sav", client_settings)
+                .await
+        }
+        .await;
+
+        let _ = clone_from_account.kill_session().await;
+        let _ = clone_to_account.kill_session().await;
+        copied?;
+
+        Ok(AccountOutcome::SettingsCloned {
+            from: clone_from_username.to_string(),
+            to: clone_to_username.to_string(),
+        })
+    }
+
+    //the gui reads the configuration while rendering, it is not locked while the launcher exits
+    pub async fn swap_account(&self, account_id: &str) -> Result<AccountOutcome, EpicError> {
+        let (descriptor, launcher, close_launcher, restart, executable) = {
+            let configuration = self.configuration.lock().await;
+
+            (
+                find_account(&configuration, account_id)?.clone(),
+                configuration.launcher.resolve(&configuration)?,
+                configuration.close_epic_games_launcher_on_swap,
+                configuration.restart_launcher_on_swap,
+                Some(&configuration.launcher_executable_path)
+                    .filter(|path| !path.is_empty())
+                    .map(PathBuf::from),
+            )
+        };
+
+        self.progress(AccountProgress::LoggingIn(descriptor.display_name.clone()));
+        let account = descriptor.login_as_launcher().await?;
+
+        let session = match account.get_infos().await {
+            Ok(details) => LauncherSession { details, account },
+            Err(error) => {
+                let _ = account.kill_session().await;
+                return Err(error);
+            }
+        };
+
+        let written = async {
+            //the launcher saves its own session when it exits, it has to be closed before the write
+            if (close_launcher || restart) && launcher.is_running() {
+                self.progress(AccountProgress::ClosingLauncher(launcher.kind()));
+                let pids = launcher.stop()?;
+
+                self.progress(AccountProgress::WaitingForLauncherExit(launcher.kind()));
+                wait_for_exit(pids, &launcher.kind().to_string()).await?;
+            }
+
+            self.progress(AccountProgress::WritingLauncherSession);
+            launcher.write_active_account(&session)?;
+
+            self.progress(AccountProgress::VerifyingLauncherSession);
+            let written = launcher.read_active_account()?;
+            if Some(&written.token) != session.account.refresh_token().as_ref() {
+                return Err(EpicError::new(
+                    EpicErrorKind::InvalidResponse,
+                    Some("The launcher session was not saved, try again with the launcher closed"),
+                ));
+            }
+
+            Ok(())
+        }
+        .await;
+
+        //the session belongs to the launcher once it is written, until then nothing else uses it
+        if let Err(error) = written {
+            let _ = session.account.kill_session().await;
+            return Err(error);
+        }
+
+        if restart {
+            self.progress(AccountProgress::StartingLauncher(launcher.kind()));
+            launcher.start(executable.as_deref())?;
+        }
+
+        self.emit(AccountEvent::CurrentAccountChanged(Some(descriptor.display_name.clone())));
+
+        Ok(AccountOutcome::Swapped(descriptor, launcher.kind()))
+    }
+
+    //the game logs in with the exchange code, the launcher session is left untouched
+    pub async fn play_as(&self, account_id: &str) -> Result<AccountOutcome, EpicError> {
+        let (descriptor, executable) = {
+            let configuration = self.configuration.lock().await;
+            (find_account(&configuration, account_id)?.clone(), game::game_executable(&configuration)?)
+        };
+
+        //epic only keeps one game session per account, a second instance would kick the first one
+        if self.game_instances.is_in_game(account_id) {
+            return Err(EpicError::new(
+                EpicErrorKind::Other,
+                Some(format!("{} is already in game", descriptor.display_name)),
+            ));
+        }
+
+        self.progress(AccountProgress::LoggingIn(descriptor.display_name.clone()));
+        let session = device_auth_of(&descriptor)?.login().await?;
+
+        //the exchange code stays valid once the session which asked for it is killed
+        self.progress(AccountProgress::RequestingExchangeCode);
+        let exchange_code = session.exchange_code().await;
+        let _ = session.kill_session().await;
+        let exchange_code = exchange_code?;
+
+        self.progress(AccountProgress::StartingGame(descriptor.display_name.clone()));
+        let arguments = game::fortnite_arguments(&exchange_code, account_id, &descriptor.display_name, &descriptor.launch_args);
+        let child = game::launch_game(&executable, &arguments)?;
+        let pid = child.id();
+
+        self.game_instances.track(account_id, child);
+        self.emit(AccountEvent::GameInstancesChanged(self.game_instances.in_game()));
+
+        Ok(AccountOutcome::GameStarted(descriptor, pid))
+    }
+
+    pub async fn stop_game(&self, account_id: &str) -> Result<AccountOutcome, EpicError> {
+        let descriptor = find_account(&*self.configuration.lock().await, account_id)?.clone();
+
+        self.progress(AccountProgress::StoppingGame(descriptor.display_name.clone()));
+        let pids = self.game_instances.stop(account_id)?;
+        wait_for_exit(pids, &descriptor.display_name).await?;
+
+        self.game_instances.refresh();
+        self.emit(AccountEvent::GameInstancesChanged(self.game_instances.in_game()));
+
+        Ok(AccountOutcome::GameStopped(descriptor))
+    }
+
+    pub async fn set_launch_args(&self, account_id: &str, launch_args: &str) -> Result<AccountOutcome, EpicError> {
+        let mut configuration = self.configuration.lock().await;
+        let account = find_account_mut(&mut configuration, account_id)?;
+
+        account.launch_args = launch_args.trim().to_string();
+        let descriptor = account.clone();
+        let _ = configuration.flush();
+
+        Ok(AccountOutcome::LaunchArgumentsSaved(descriptor))
+    }
+
+    //the configuration is not locked during the logins, the gui reads it while rendering
+    async fn login_every_account(
+        &self,
+        progress: fn(String) -> AccountProgress,
+    ) -> Result<Vec<(String, Result<EpicAccount, EpicError>)>, EpicError> {
+        //a locked vault would make every account look broken
+        if secret_store::is_locked() {
+            return Err(EpicError::new(EpicErrorKind::CipherError, Some("Your accounts are locked")));
+        }
+
+        let accounts = self.configuration.lock().await.accounts.clone();
+        let mut logins = Vec::new();
+
+        for account in accounts.iter() {
+            let Some(account_id) = account.account_id() else {
+                continue;
+            };
+
+            self.progress(progress(account.display_name.clone()));
+            logins.push((account_id.to_string(), device_auth_of(account)?.login().await));
+        }
+
+        Ok(logins)
+    }
+
+    //tries the device auth of every account and records why it failed
+    pub async fn check_health(&self) -> Result<AccountOutcome, EpicError> {
+        let logins = self.login_every_account(AccountProgress::CheckingAccount).await?;
+
+        for epic_account in logins.iter().filter_map(|(_, login)| login.as_ref().ok()) {
+            let _ = epic_account.kill_session().await;
+        }
+
+        let mut configuration = self.configuration.lock().await;
+        let (mut healthy, mut broken, mut unreachable) = (0, 0, 0);
+
+        for (account_id, login) in logins.iter() {
+            let health = match login {
+                Ok(_) => AccountHealth::Healthy,
+                Err(error) => AccountHealth::classify(error),
+            };
+
+            match health {
+                AccountHealth::Healthy => healthy += 1,
+                health if health.is_broken() => broken += 1,
+                _ => unreachable += 1,
+            }
+
+            //the account may have been removed in the meantime
+            if let Ok(account) = find_account_mut(&mut configuration, account_id) {
+                match health {
+                    AccountHealth::Healthy => account.mark_verified(),
+                    health => account.health = health,
+                }
+            }
+        }
+
+        let _ = configuration.flush();
+        self.accounts_changed(&configuration);
+
+        Ok(AccountOutcome::HealthChecked { healthy, broken, unreachable })
+    }
+
+    //logs in, completing the corrective action epic asks for if it can be done from here
+    pub async fn complete_corrective_action(&self, account_id: &str) -> Result<AccountOutcome, EpicError> {
+        let descriptor = find_account(&*self.configuration.lock().await, account_id)?.clone();
+        let mut device_auth = device_auth_of(&descriptor)?;
+
+        self.progress(AccountProgress::CompletingCorrectiveAction(descriptor.display_name.clone()));
+        device_auth.uncipher_secret()?;
+        let session = epic::token_with_corrective_actions(Token::DeviceAuth(&device_auth), get_client!("fortniteIOSGameClient")).await?;
+        let _ = session.kill_session().await;
+
+        let mut configuration = self.configuration.lock().await;
+        let account = find_account_mut(&mut configuration, account_id)?;
+        account.mark_verified();
+        let descriptor = account.clone();
+
+        let _ = configuration.flush();
+        self.accounts_changed(&configuration);
+
+        Ok(AccountOutcome::CorrectiveActionCompleted(descriptor))
+    }
+
+    //the device auth stays valid, the manager can still log in afterwards
+    pub async fn log_out_everywhere(&self, account_id: &str) -> Result<AccountOutcome, EpicError> {
+        let descriptor = find_account(&*self.configuration.lock().await, account_id)?.clone();
+
+        self.progress(AccountProgress::LoggingOutEverywhere(descriptor.display_name.clone()));
+        let session = device_auth_of(&descriptor)?.login().await?;
+        let killed = session.kill_other_sessions().await;
+        let _ = session.kill_session().await;
+        killed?;
+
+        Ok(AccountOutcome::LoggedOutEverywhere(descriptor))
+    }
+
+    //the device auths epic knows for the account, the one the manager uses included
+    pub async fn device_auths(&self, account_id: &str) -> Result<Vec<DeviceAuthInfo>, EpicError> {
+        let descriptor = find_account(&*self.configuration.lock().await, account_id)?.clone();
+
+        self.progress(AccountProgress::ListingDeviceAuths(descriptor.display_name.clone()));
+        let session = device_auth_of(&descriptor)?.login().await?;
+        let device_auths = session.get_device_auths().await;
+        let _ = session.kill_session().await;
+
+        device_auths
+    }
+
+    //removing the account is the way to delete the device auth the manager logs in with
+    pub async fn delete_device_auth(&self, account_id: &str, device_id: &str) -> Result<AccountOutcome, EpicError> {
+        let descriptor = find_account(&*self.configuration.lock().await, account_id)?.clone();
+
+        if device_auth_of(&descriptor)?.device_id == device_id {
+            return Err(EpicError::new(
+                EpicErrorKind::Other,
+                Some(format!("{} logs in with this device auth, remove the account instead", descriptor.display_name)),
+            ));
+        }
+
+        self.progress(AccountProgress::DeletingDeviceAuth(descriptor.display_name.clone()));
+        delete_device_auth_as(&descriptor, device_id).await?;
+
+        Ok(AccountOutcome::DeviceAuthDeleted(descriptor))
+    }
+
+    //the device auths replaced by a merged duplicate, deleted with a session of the account which replaced them
+    pub async fn delete_superseded_device_auths(&self) -> Result<AccountOutcome, EpicError> {
+        if secret_store::is_locked() {
+            return Err(EpicError::new(EpicErrorKind::CipherError, Some("Your accounts are locked")));
+        }
+
+        let (superseded, accounts) = {
+            let configuration = self.configuration.lock().await;
+            (configuration.superseded_device_auths.clone(), configuration.accounts.clone())
+        };
+
+        let mut handled: Vec<SupersededDeviceAuth> = Vec::new();
+        let (mut deleted, mut failed) = (0, 0);
+
+        for device_auth in superseded.iter() {
+            //a removed account can't log in to delete it anymore
+            let Some(account) = accounts.iter().find(|x| x.account_id() == Some(device_auth.account_id.as_str())) else {
+                handled.push(device_auth.clone());
+                continue;
+            };
+
+            self.progress(AccountProgress::DeletingDeviceAuth(account.display_name.clone()));
+            match delete_device_auth_as(account, &device_auth.device_id).await {
+                Ok(_) => deleted += 1,
+                Err(error) if error.error_code() == Some(error_codes::DEVICE_AUTH_NOT_FOUND) => {}
+                Err(error) => {
+                    eprintln!(
+                        "Failed to delete the replaced device auth {} of {} : {}",
+                        device_auth.device_id, account.display_name, error
+                    );
+                    failed += 1;
+                    continue;
+                }
+            }
+
+            handled.push(device_auth.clone());
+        }
+
+        let mut configuration = self.configuration.lock().await;
+        configuration.superseded_device_auths.retain(|x| !handled.contains(x));
+
+        let _ = configuration.flush();
+        self.emit(AccountEvent::SupersededDeviceAuthsChanged(configuration.superseded_device_auths.len()));
+
+        Ok(AccountOutcome::SupersededDeviceAuthsDeleted { deleted, failed })
+    }
+
+    //epic keeps the device auth after a rename, the name and email are asked again for every account
+    pub async fn refresh_profiles(&self) -> Result<AccountOutcome, EpicError> {
+        let logins = self.login_every_account(AccountProgress::RefreshingProfile).await?;
+        let mut profiles = Vec::new();
+
+        for (account_id, login) in logins {
+            let details = match login {
+                Ok(epic_account) => {
+                    let details = epic_account.get_infos().await;
+                    let _ = epic_account.kill_session().await;
+                    details
+                }
+                Err(error) => Err(error),
+            };
+
+            profiles.push((account_id, details));
+        }
+
+        let mut configuration = self.configuration.lock().await;
+        let (mut changed, mut failed) = (0, 0);
+
+        for (account_id, details) in profiles.iter() {
+            let Ok(account) = find_account_mut(&mut configuration, account_id) else {
+                continue;
+            };
+
+            match details {
+                Ok(details) => {
+                    if account.update_profile(details) {
+                        changed += 1;
+                    }
+                }
+                Err(error) => {
+                    eprintln!("Failed to refresh the profile of {} : {}", account.display_name, error);
+                    account.health = AccountHealth::classify(error);
+                    failed += 1;
+                }
+            }
+        }
+
+        let _ = configuration.flush();
+        self.accounts_changed(&configuration);
+
+        Ok(AccountOutcome::ProfilesRefreshed { changed, failed })
+    }
+
+    pub async fn remove_account(&self, account_id: &str, mode: RemovalMode) -> Result<AccountOutcome, EpicError> {
+        let descriptor = find_account(&*self.configuration.lock().await, account_id)?.clone();
+
+        let revoked = match (mode, &descriptor.device_auth) {
+            (RemovalMode::RevokeOnEpic, Some(_)) => {
+                //a locked vault would make the device auth look broken and skip the revocation
+                if secret_store::is_locked() {
+                    return Err(EpicError::new(
+                        EpicErrorKind::CipherError,
+                        Some(format!("Your accounts are locked, unlock them to remove {}", descriptor.display_name)),
+                    ));
+                }
+
+                self.progress(AccountProgress::DeletingDeviceAuth(descriptor.display_name.clone()));
+
+                match revoke_device_auth(&descriptor).await {
+                    Ok(_) => true,
+                    Err(error) => match AccountHealth::classify(&error) {
+                        //the device auth already stopped working, there is nothing left to revoke
+                        AccountHealth::Revoked | AccountHealth::Banned => false,
+                        AccountHealth::CipherFailure => {
+                            return Err(EpicError::new(
+                                EpicErrorKind::CipherError,
+                                Some(format!(
+                                    "The device auth of {} can't be decrypted to delete it on Epic, use Remove locally only",
+                                    descriptor.display_name
+                                )),
+                            ));
+                        }
+                        _ => {
+                            return Err(EpicError::new(
+                                EpicErrorKind::Other,
+                                Some(format!(
+                                    "Failed to delete the device auth of {} on Epic, remove it locally only to keep it there : {}",
+                                    descriptor.display_name, error
+                                )),
+                            ));
+                        }
+                    },
+                }
+            }
+            _ => false,
+        };
+
+        let mut configuration = self.configuration.lock().await;
+
+        let position = configuration
+            .accounts
+            .iter()
+            .position(|x| x.account_id() == Some(account_id))
+            .ok_or(not_found(account_id))?;
+
+        let descriptor = configuration.accounts.remove(position);
+        let _ = configuration.flush();
+        self.accounts_changed(&configuration);
+
+        match revoked {
+            true => Ok(AccountOutcome::Removed(descriptor)),
+            false => Ok(AccountOutcome::RemovedLocally(descriptor)),
+        }
+    }
+
+    //device auths are unsealed, the output must be handled like a password
+    pub async fn export_accounts(&self) -> Result<Vec<ExportedAccount>, EpicError> {
+        let configuration = self.configuration.lock().await;
+
+        configuration
+            .accounts
+            .iter()
+            .filter_map(|account| account.device_auth.clone().map(|x| (account, x)))
+            .map(|(account, mut device_auth)| {
+                device_auth.uncipher_secret()?;
+
+                Ok(ExportedAccount {
+                    display_name: account.display_name.clone(),
+                    account_id: device_auth.account_id.clone(),
+                    device_id: device_auth.device_id.clone(),
+                    secret: device_auth.secret.clone(),
+                })
+            })
+            .collect()
+    }
+
+    pub async fn kill_epic_games_launcher() -> Result<AccountOutcome, EpicError> {
+        wait_for_exit(EpicGamesLauncher.stop()?, EPIC_GAMES_LAUNCHER_PROCESS).await?;
+
+        Ok(AccountOutcome::LauncherKilled)
+    }
+}
@@ -0,0 +1,413 @@
+use std::{fmt::Display, path::PathBuf, sync::Arc};
+
+use tokio::{
+    runtime::Runtime,
+    sync::{mpsc::UnboundedReceiver, Mutex},
+};
+
+use crate::{
+    accounts::{AccountEvent, AccountOutcome, AccountService, RemovalMode},
+    config::Configuration,
+    egl::get_decryption_keys,
+    epic::{DeviceAuth, Token},
+    epic_clients::AuthClient,
+    loopback::LoopbackListener,
+    secret_store,
+};
+
+//the master password can't be typed in a script, it has to come from the environment
+pub const MASTER_PASSWORD_ENV_VAR: &str = "ALT_MANAGER_MASTER_PASSWORD";
+
+const USAGE: &str = "Usage: alt-manager-cli <command>
+
+Commands:
+  list                                  List the saved accounts
+  add --device-code                     Add an account through epicgames.com/activate
+  add --browser [--client <name>]       Add an account by logging in on a local page in the browser
+  add --device-auth --account-id <id> --device-id <id> --secret <secret> [--client <name>]
+                                        Add an account from an existing device auth
+  link                                  Add the account currently logged in the selected launcher
+  swap <name>                           Log the selected launcher into an account
+  play <name>                           Start Fortnite as an account without the launcher
+  remove <name> [--local-only]          Remove an account and delete its device auth on Epic,
+                                        --local-only keeps the device auth valid on Epic
+  refresh                               Update the display names and emails from Epic
+  check                                 Check that every account can still log in
+  fix <name>                            Complete the action Epic requires before an account can log in
+  logout <name>                         Log an account out of every launcher, game and device
+  device-auths <name>                   List the device auths Epic knows for an account
+  delete-device-auth <name> <device-id> Delete a device auth the manager does not use
+  delete-replaced-device-auths          Delete the device auths replaced by duplicated accounts
+  clone-settings <from> <to>            Copy the Fortnite settings of an account to another one
+  kill-egl                              Close EpicGamesLauncher
+  export [--output <path>]              Export the device auths as JSON (secrets are NOT encrypted)
+  help                                  Show this message
+
+Accounts are given by display name or by account id, see list.
+
+Running alt-manager without a command starts the graphical interface, alt-manager <command>
+works too but has no console on Windows.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    List,
+    AddDeviceCode,
+    AddBrowser(String),
+    AddDeviceAuth {
+        account_id: String,
+        device_id: String,
+        secret: String,
+        client: String,
+    },
+    Link,
+    Swap(String),
+    Play(String),
+    Remove(String, RemovalMode),
+    Refresh,
+    Check,
+    Fix(String),
+    Logout(String),
+    DeviceAuths(String),
+    DeleteDeviceAuth(String, String),
+    DeleteReplacedDeviceAuths,
+    CloneSettings(String, String),
+    KillEgl,
+    Export(Option<PathBuf>),
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliError {
+    UnknownCommand(String),
+    MissingArgument(&'static str),
+    UnexpectedArgument(String),
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::UnknownCommand(command) => write!(f, "Unknown command {}", command),
+            CliError::MissingArgument(argument) => write!(f, "Missing argument {}", argument),
+            CliError::UnexpectedArgument(argument) => write!(f, "Unexpected argument {}", argument),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+//takes the value following a --flag
+fn flag_value(args: &mut std::slice::Iter<'_, String>, name: &'static str) -> Result<String, CliError> {
+    args.next().cloned().ok_or(CliError::MissingArgument(name))
+}
+
+fn positional(args: &mut std::slice::Iter<'_, String>, name: &'static str) -> Result<String, CliError> {
+    match args.next() {
+        Some(value) if !value.starts_with("--") => Ok(value.clone()),
+        Some(value) => Err(CliError::UnexpectedArgument(value.clone())),
+        None => Err(CliError::MissingArgument(name)),
+    }
+}
+
+fn no_more_arguments(args: &mut std::slice::Iter<'_, String>) -> Result<(), CliError> {
+    match args.next() {
+        Some(value) => Err(CliError::UnexpectedArgument(value.clone())),
+        None => Ok(()),
+    }
+}
+
+fn parse_add(args: &mut std::slice::Iter<'_, String>) -> Result<Command, CliError> {
+    let mut device_code = false;
+    let mut browser = false;
+    let mut device_auth = false;
+    let mut account_id = None;
+    let mut device_id = None;
+    let mut secret = None;
+    let mut client = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--device-code" => device_code = true,
+            "--browser" => browser = true,
+            "--device-auth" => device_auth = true,
+            "--account-id" => account_id = Some(flag_value(args, "--account-id")?),
+            "--device-id" => device_id = Some(flag_value(args, "--device-id")?),
+            "--secret" => secret = Some(flag_value(args, "--secret")?),
+            "--client" => client = Some(flag_value(args, "--client")?),
+            _ => return Err(CliError::UnexpectedArgument(arg.clone())),
+        }
+    }
+
+    match (device_code, browser, device_auth) {
+        (true, false, false) => Ok(Command::AddDeviceCode),
+        (false, true, false) => Ok(Command::AddBrowser(client.unwrap_or("launcherAppClient2".to_string()))),
+        (false, false, true) => Ok(Command::AddDeviceAuth {
+            account_id: account_id.ok_or(CliError::MissingArgument("--account-id"))?,
+            device_id: device_id.ok_or(CliError::MissingArgument("--device-id"))?,
+            secret: secret.ok_or(CliError::MissingArgument("--secret"))?,
+            client: client.unwrap_or("fortniteIOSGameClient".to_string()),
+        }),
+        _ => Err(CliError::MissingArgument("--device-code, --browser or --device-auth")),
+    }
+}
+
+pub fn parse(args: &[String]) -> Result<Command, CliError> {
+    let mut args = args.iter();
+
+    let command = match args.next().map(|x| x.as_str()) {
+        None | Some("help") | Some("--help") | Some("-h") => return Ok(Command::Help),
+        Some("list") => Command::List,
+        Some("add") => return parse_add(&mut args),
+        Some("link") => Command::Link,
+        Some("swap") => Command::Swap(positional(&mut args, "<name>")?),
+        Some("play") => Command::Play(positional(&mut args, "<name>")?),
+        Some("remove") => {
+            let account = positional(&mut args, "<name>")?;
+
+            match args.next().map(|x| x.as_str()) {
+                Some("--local-only") => Command::Remove(account, RemovalMode::LocalOnly),
+                Some(arg) => return Err(CliError::UnexpectedArgument(arg.to_string())),
+                None => Command::Remove(account, RemovalMode::RevokeOnEpic),
+            }
+        }
+        Some("refresh") => Command::Refresh,
+        Some("check") => Command::Check,
+        Some("fix") => Command::Fix(positional(&mut args, "<name>")?),
+        Some("logout") => Command::Logout(positional(&mut args, "<name>")?),
+        Some("device-auths") => Command::DeviceAuths(positional(&mut args, "<name>")?),
+        Some("delete-device-auth") => Command::DeleteDeviceAuth(
+            positional(&mut args, "<name>")?,
+            positional(&mut args, "<device-id>")?,
+        ),
+        Some("delete-replaced-device-auths") => Command::DeleteReplacedDeviceAuths,
+        Some("clone-settings") => Command::CloneSettings(
+            positional(&mut args, "<from>")?,
+            positional(&mut args, "<to>")?,
+        ),
+        Some("kill-egl") => Command::KillEgl,
+        Some("export") => match args.next().map(|x| x.as_str()) {
+            Some("--output") => Command::Export(Some(PathBuf::from(flag_value(&mut args, "--output")?))),
+            Some(arg) => return Err(CliError::UnexpectedArgument(arg.to_string())),
+            None => Command::Export(None),
+        },
+        Some(command) => return Err(CliError::UnknownCommand(command.to_string())),
+    };
+
+    no_more_arguments(&mut args)?;
+
+    Ok(command)
+}
+
+fn load_configuration() -> Result<Configuration, Box<dyn std::error::Error>> {
+    let configuration = Configuration::new()?;
+
+    if secret_store::is_locked() {
+        let password = std::env::var(MASTER_PASSWORD_ENV_VAR).map_err(|_| {
+            format!("Your accounts are locked, set {} to unlock them", MASTER_PASSWORD_ENV_VAR)
+        })?;
+
+        secret_store::unlock_master_vault(password.as_bytes())?;
+    }
+
+    if !configuration.superseded_device_auths.is_empty() {
+        eprintln!(
+            "{} replaced device auths are still valid on Epic, run delete-replaced-device-auths to delete them",
+            configuration.superseded_device_auths.len()
+        );
+    }
+
+    Ok(configuration)
+}
+
+async fn print_events(mut events: UnboundedReceiver<AccountEvent>) {
+    while let Some(event) = events.recv().await {
+        match event {
+            AccountEvent::Progress(progress) => eprintln!("{}...", progress),
+            AccountEvent::DeviceCode(authorization) => println!(
+                "Open {} and enter the code {}",
+                authorization.verification_uri, authorization.user_code
+            ),
+            AccountEvent::AccountsChanged(_)
+            | AccountEvent::CurrentAccountChanged(_)
+            | AccountEvent::GameInstancesChanged(_)
+            | AccountEvent::SupersededDeviceAuthsChanged(_) => {}
+        }
+    }
+}
+
+async fn run_workflow(account_service: &AccountService, command: Command) -> Result<AccountOutcome, Box<dyn std::error::Error>> {
+    let outcome = match command {
+        Command::AddDeviceCode => account_service.add_with_device_code().await?,
+        Command::AddBrowser(client) => {
+            let client = AuthClient::get(&client).ok_or(format!("Unknown client {}", client))?;
+            let listener = LoopbackListener::bind(&client)?;
+
+            println!("Open {} in your browser to log in", listener.local_url());
+            account_service.add_with_browser(&listener, client).await?
+        }
+        Command::AddDeviceAuth { account_id, device_id, secret, client } => {
+            let client = AuthClient::get(&client).ok_or(format!("Unknown client {}", client))?;
+
+            let device_auth = DeviceAuth {
+                account_id,
+                device_id,
+                secret,
+            };
+
+            account_service.add_account(Token::DeviceAuth(&device_auth), client).await?
+        }
+        Command::Link => {
+            get_decryption_keys().await;
+            account_service.link_current_account().await?
+        }
+        Command::Swap(account) => {
+            get_decryption_keys().await;
+            account_service.swap_account(&account_service.resolve_account(&account).await?).await?
+        }
+        Command::Play(account) => account_service.play_as(&account_service.resolve_account(&account).await?).await?,
+        Command::Remove(account, mode) => {
+            account_service
+                .remove_account(&account_service.resolve_account(&account).await?, mode)
+                .await?
+        }
+        Command::Refresh => account_service.refresh_profiles().await?,
+        Command::Check => account_service.check_health().await?,
+        Command::Fix(account) => {
+            account_service
+                .complete_corrective_action(&account_service.resolve_account(&account).await?)
+                .await?
+        }
+        Command::Logout(account) => {
+            account_service
+                .log_out_everywhere(&account_service.resolve_account(&account).await?)
+                .await?
+        }
+        Command::DeleteDeviceAuth(account, device_id) => {
+            account_service
+                .delete_device_auth(&account_service.resolve_account(&account).await?, &device_id)
+                .await?
+        }
+        Command::DeleteReplacedDeviceAuths => account_service.delete_superseded_device_auths().await?,
+        Command::CloneSettings(from, to) => {
+            let from = account_service.resolve_account(&from).await?;
+            let to = account_service.resolve_account(&to).await?;
+            account_service.clone_settings(&from, &to).await?
+        }
+        _ => unreachable!("{:?} is not an account workflow", command),
+    };
+
+    Ok(outcome)
+}
+
+pub async fn execute(command: Command) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        Command::Help => {
+            println!("{}", USAGE);
+        }
+        Command::KillEgl => {
+            println!("{}", AccountService::kill_epic_games_launcher().await?);
+        }
+        Command::List => {
+            let configuration = load_configuration()?;
+
+            for account in configuration.accounts.iter() {
+                match &account.device_auth {
+                    Some(device_auth) => println!("{}\t{}\t{}", account.display_name, device_auth.account_id, account.health),
+                    None => println!("{}", account.display_name),
+                }
+            }
+        }
+        Command::DeviceAuths(account) => {
+            let account_service = AccountService::new(Arc::new(Mutex::new(load_configuration()?)));
+            let account_id = account_service.resolve_account(&account).await?;
+
+            let current_device_id = account_service
+                .configuration()
+                .lock()
+                .await
+                .accounts
+                .iter()
+                .find(|x| x.account_id() == Some(account_id.as_str()))
+                .and_then(|x| x.device_auth.as_ref().map(|x| x.device_id.clone()));
+
+            for device_auth in account_service.device_auths(&account_id).await? {
+                let created = device_auth.created.and_then(|x| x.date_time).unwrap_or_default();
+                let in_use = match current_device_id.as_deref() == Some(device_auth.device_id.as_str()) {
+                    true => "in use",
+                    false => "",
+                };
+
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    device_auth.device_id,
+                    created,
+                    device_auth.user_agent.unwrap_or_default(),
+                    in_use
+                );
+            }
+        }
+        Command::Export(output) => {
+            let account_service = AccountService::new(Arc::new(Mutex::new(load_configuration()?)));
+
+            let exported = account_service.export_accounts().await?;
+            let data = zeroize::Zeroizing::new(serde_json::to_string_pretty(&exported)?);
+
+            match output {
+                Some(path) => {
+                    secret_store::write_private_file(&path, data.as_bytes())
+                        .map_err(|error| format!("Failed to write {} : {}", path.display(), error))?;
+                    eprintln!("Exported {} accounts to {}", exported.len(), path.display());
+                }
+                None => println!("{}", data.as_str()),
+            }
+        }
+        command => {
+            let (event_sender, events) = tokio::sync::mpsc::unbounded_channel();
+            let printer = tokio::spawn(print_events(events));
+
+            let account_service = AccountService::new(Arc::new(Mutex::new(load_configuration()?)))
+                .with_events(event_sender);
+            let result = run_workflow(&account_service, command).await;
+
+            //the printer stops once every sender is dropped
+            drop(account_service);
+            let _ = printer.await;
+
+            println!("{}", result?);
+        }
+    }
+
+    Ok(())
+}
+
+//release builds of alt-manager use the windows subsystem, borrow the console of the shell that started it
+#[cfg(windows)]
+fn attach_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+pub fn run(args: &[String]) -> i32 {
+    #[cfg(windows)]
+    attach_console();
+
+    let command = match parse(args) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            return 2;
+        }
+    };
+
+    let rt = Runtime::new().expect("Unable to create Runtime");
+
+    match rt.block_on(execute(command)) {
+        Ok(_) => 0,
+        Err(error) => {
+            eprintln!("{}", error);
+            1
+        }
+    }
+}
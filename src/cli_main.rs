@@ -0,0 +1,28 @@
+//the cli as a console program, release builds of alt-manager have no console on windows
+//and the shell does not wait for them. it shares the modules of the gui, what only the gui uses is unused here
+#![allow(dead_code)]
+
+mod accounts;
+mod cli;
+mod config;
+mod decrypt;
+mod egl;
+mod endpoints;
+mod epic;
+mod process;
+mod secret_store;
+mod version;
+mod legendary;
+mod heroic;
+mod launchers;
+mod epic_clients;
+mod game;
+mod health;
+mod http;
+mod loopback;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    std::process::exit(cli::run(&args));
+}
@@ -1,6 +1,10 @@
 use crate::{
     egl::RememberMeEntry,
-    epic::{self, AccountDescriptor, EpicAccount}, launchers::Launchers, get_client,
+    endpoints::{self, EpicEndpoints},
+    epic::{self, AccountDescriptor, DeviceAuth, EpicAccount, EpicError}, launchers::Launchers, get_client,
+    health::AccountHealth,
+    http::{self, HttpSettings},
+    secret_store::{self, SecretStoreKind},
 };
 use std::{
     fmt::Display,
@@ -15,16 +19,45 @@ pub struct Configuration {
     pub launcher:Launchers,
     #[serde(default)]
     pub legendary_path:String,
+    #[serde(default)]
+    pub heroic_path:String,
     #[serde(default = "default_version")]
     pub version:String,
     #[serde(default)]
-    pub close_epic_games_launcher_on_swap:bool
+    pub close_epic_games_launcher_on_swap:bool,
+    #[serde(default)]
+    pub restart_launcher_on_swap:bool,
+    #[serde(default)]
+    pub launcher_executable_path:String,
+    #[serde(default)]
+    pub game_path:String,
+    #[serde(default)]
+    pub endpoints:EpicEndpoints,
+    #[serde(default)]
+    pub http:HttpSettings,
+    #[serde(default)]
+    pub secret_store:SecretStoreKind,
+    #[serde(default = "default_auto_lock_minutes")]
+    pub auto_lock_minutes:u64,
+    #[serde(default)]
+    pub superseded_device_auths:Vec<SupersededDeviceAuth>
 }
 
 fn default_version() -> String {
     crate::version::get_program_version().to_string()
+}
+
+fn default_auto_lock_minutes() -> u64 {
+    5
 }  
 
+//a device auth no account logs in with anymore, it stays here until it is deleted on epic
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct SupersededDeviceAuth {
+    pub account_id: String,
+    pub device_id: String,
+}
+
 pub enum AddAccountProvider<'a> {
     RememberMeEntry(&'a RememberMeEntry),
     EpicAccount(&'a EpicAccount),
@@ -65,10 +98,10 @@ impl Configuration {
 
     fn upgrade(&mut self) {
         let version = self.version.to_string();
-        println!("version : {}", version);
+        eprintln!("version : {}", version);
         match version.as_str() {
             "0.1.0" => {
-                println!("Updating configuration from version 0.1.0 to 0.1.1");
+                eprintln!("Updating configuration from version 0.1.0 to 0.1.1");
                 self.accounts.iter_mut().for_each(|x| {
                     if let Some(device_auth) = &mut x.device_auth {
                         if let Ok(_) = device_auth.uncipher_secret_xor() {
@@ -82,10 +115,36 @@ impl Configuration {
             _ => {}
         }
 
+        if let Err(error) = self.migrate_secrets() {
+            eprintln!("Failed to migrate secrets : {}", error);
+        }
+
+        //stdout is the output of cli commands like export
+        for account in self.remove_accounts_without_device_auth() {
+            eprintln!("Removed {} which has no device auth, add it again to use it", account.display_name);
+        }
+
+        let count = self.accounts.len();
+        let superseded = self.superseded_device_auths.len();
+        self.deduplicate_accounts();
+        if count > self.accounts.len() {
+            eprintln!("Removed {} duplicated accounts", count - self.accounts.len());
+        }
+
+        //the vault may still be locked, the user deletes them from the manager
+        if superseded < self.superseded_device_auths.len() {
+            eprintln!(
+                "{} device auths were replaced by duplicated accounts, delete them from the manager",
+                self.superseded_device_auths.len() - superseded
+            );
+        }
+
+        let _ = self.flush();
+
         self.version = crate::version::get_program_version().to_string();
     }
 
-    fn get_path() -> PathBuf {
+    pub(crate) fn get_path() -> PathBuf {
         if let Ok(program_data_path_str) = std::env::var("PROGRAMDATA") {
             let folder_path = PathBuf::from(program_data_path_str).join("AltManager");
 
@@ -107,8 +166,109 @@ impl Configuration {
         self.accounts = data.accounts.clone();
         self.launcher = data.launcher.clone();
         self.legendary_path = data.legendary_path.clone();
+        self.heroic_path = data.heroic_path.clone();
         self.version = data.version.clone();
         self.close_epic_games_launcher_on_swap = data.close_epic_games_launcher_on_swap;
+        self.restart_launcher_on_swap = data.restart_launcher_on_swap;
+        self.launcher_executable_path = data.launcher_executable_path.clone();
+        self.game_path = data.game_path.clone();
+        self.endpoints = data.endpoints.clone();
+        self.http = data.http.clone();
+        self.secret_store = data.secret_store;
+        self.auto_lock_minutes = data.auto_lock_minutes;
+        self.superseded_device_auths = data.superseded_device_auths.clone();
+
+        endpoints::set_endpoints(self.endpoints.clone());
+        http::set_settings(self.http.clone());
+        secret_store::set_active_store(self.secret_store);
+        self.apply_auto_lock();
+    }
+
+    //0 disables the auto lock
+    pub fn apply_auto_lock(&self) {
+        secret_store::set_auto_lock_after(match self.auto_lock_minutes {
+            0 => None,
+            minutes => Some(std::time::Duration::from_secs(minutes * 60)),
+        });
+    }
+
+    //switch to another secret store and reseal every account, rollback on failure
+    pub fn set_secret_store(&mut self, kind: SecretStoreKind) -> Result<(), EpicError> {
+        let previous_store = self.secret_store;
+        let previous_accounts = self.accounts.clone();
+
+        self.secret_store = kind;
+        secret_store::set_active_store(kind);
+
+        if let Err(error) = self.migrate_secrets() {
+            self.accounts = previous_accounts;
+            self.secret_store = previous_store;
+            secret_store::set_active_store(previous_store);
+            return Err(error);
+        }
+
+        let _ = self.flush();
+        Ok(())
+    }
+
+    //reseal every secret which is not sealed by the selected secret store
+    pub fn migrate_secrets(&mut self) -> Result<(), EpicError> {
+        for account in self.accounts.iter_mut() {
+            if let Some(device_auth) = &mut account.device_auth {
+                if !secret_store::needs_migration(&device_auth.secret) {
+                    continue;
+                }
+
+                //work on a copy so a failure never leaves a plain secret in the configuration
+                let mut migrated = device_auth.clone();
+                migrated.uncipher_secret()?;
+                migrated.cipher_secret()?;
+                *device_auth = migrated;
+            }
+        }
+
+        Ok(())
+    }
+
+    //nothing can log in with them, older versions kept them when the device auth could not be created
+    pub fn remove_accounts_without_device_auth(&mut self) -> Vec<AccountDescriptor> {
+        let (accounts, removed) = std::mem::take(&mut self.accounts)
+            .into_iter()
+            .partition(|x| x.account_id().is_some());
+
+        self.accounts = accounts;
+        removed
+    }
+
+    //versions before 0.1.4 keyed accounts by display name, a renamed account was saved twice,
+    //the device auths of the merged entries are kept to be deleted on epic
+    pub fn deduplicate_accounts(&mut self) {
+        let accounts = std::mem::take(&mut self.accounts);
+        let mut merged = Vec::new();
+
+        for account in accounts.iter() {
+            //the last entry wins, like it did when it was saved
+            if let Some(previous) = self.accounts.iter().find(|x| x.account_id() == account.account_id()) {
+                merged.extend(previous.device_auth.as_ref().map(|x| SupersededDeviceAuth {
+                    account_id: x.account_id.clone(),
+                    device_id: x.device_id.clone(),
+                }));
+            }
+
+            self.insert_or_edit(account);
+        }
+
+        for superseded in merged {
+            let in_use = self
+                .accounts
+                .iter()
+                .filter_map(|x| x.device_auth.as_ref())
+                .any(|x| x.device_id == superseded.device_id);
+
+            if !in_use && !self.superseded_device_auths.contains(&superseded) {
+                self.superseded_device_auths.push(superseded);
+            }
+        }
     }
 
     fn read(&mut self) -> Result<(), Box<dyn std::error::Error>> {
@@ -139,27 +299,56 @@ impl Configuration {
             serde_json::to_string_pretty(&self)?,
         )?;
         if cfg!(debug_assertions) {
-            println!("Flushed configuration successfully !");
+            eprintln!("Flushed configuration successfully !");
         }
         Ok(())
     }
 
     pub fn insert_or_edit(&mut self, data: &AccountDescriptor) -> () {
-        if let Some(pos) = self
-            .accounts
-            .iter()
-            .position(|x| x.display_name == data.display_name)
-        {
+        if let Some(pos) = self.accounts.iter().position(|x| x == data) {
+            //re-adding an account keeps its launch arguments, the display name is refreshed
+            let launch_args = std::mem::take(&mut self.accounts[pos].launch_args);
+            let email = std::mem::take(&mut self.accounts[pos].email);
             self.accounts[pos] = data.clone();
+
+            if data.launch_args.is_empty() {
+                self.accounts[pos].launch_args = launch_args;
+            }
+
+            if data.email.is_empty() {
+                self.accounts[pos].email = email;
+            }
         } else {
             self.accounts.push(data.clone());
         }
     }
 
+    //a re-linked account gets a new device auth, returns the id of the one it replaces
+    async fn create_device_auth(&self, account: &EpicAccount) -> Result<(DeviceAuth, Option<String>), AddAccountError> {
+        let mut device_auth = account
+            .create_device_auth()
+            .await
+            .map_err(|_| AddAccountError::InvalidResponse)?;
+
+        if device_auth.cipher_secret().is_err() {
+            return Err(AddAccountError::CipherError);
+        }
+
+        let superseded = self
+            .accounts
+            .iter()
+            .filter_map(|x| x.device_auth.as_ref())
+            .find(|x| x.account_id == device_auth.account_id && x.device_id != device_auth.device_id)
+            .map(|x| x.device_id.clone());
+
+        Ok((device_auth, superseded))
+    }
+
+    //the replaced device auth stays valid on epic, it can only be deleted once the new one is saved
     pub async fn add_account<'a>(
         &mut self,
         account: AddAccountProvider<'a>,
-    ) -> Result<AccountDescriptor, AddAccountError> {
+    ) -> Result<(AccountDescriptor, Option<String>), AddAccountError> {
         match account {
             AddAccountProvider::RememberMeEntry(entry) => {
                 /*
@@ -180,23 +369,24 @@ impl Configuration {
                     Err(_) => Err(AddAccountError::FailedToExchange),
                 }?;
 
-                let mut device_auth = match account.create_device_auth().await {
-                    Ok(data) => Ok(data),
-                    Err(_) => Err(AddAccountError::InvalidResponse),
-                }?;
+                //the launcher session stays, it belongs to the launcher
+                let device_auth = self.create_device_auth(&account).await;
+                let _ = account.kill_session().await;
+                let (device_auth, superseded) = device_auth?;
 
-                if device_auth.cipher_secret().is_err() {
-                    return Err(AddAccountError::CipherError);
-                }
-
-                let descriptor = AccountDescriptor {
+                let mut descriptor = AccountDescriptor {
                     display_name: entry.display_name.clone(),
                     device_auth: Some(device_auth),
+                    launch_args: String::new(),
+                    email: entry.email.clone(),
+                    last_verified: None,
+                    health: AccountHealth::Unknown,
                 };
+                descriptor.mark_verified();
 
                 self.insert_or_edit(&descriptor);
 
-                return Ok(descriptor);
+                return Ok((descriptor, superseded));
             }
             AddAccountProvider::EpicAccount(account) => {
                 //fortnite ios ou new switch
@@ -206,6 +396,7 @@ impl Configuration {
                 */
 
                 let mut account = account.clone();
+                let mut exchanged = false;
 
                 let fortnite_ios_game_client = get_client!("fortniteIOSGameClient");
 
@@ -214,24 +405,28 @@ impl Configuration {
                         .exchange_to(fortnite_ios_game_client)
                         .await
                         .map_err(|_| AddAccountError::FailedToExchange)?;
+                    exchanged = true;
                 }
 
-                let mut device_auth = match account.create_device_auth().await {
-                    Ok(data) => Ok(data),
-                    Err(_) => Err(AddAccountError::InvalidResponse),
-                }?;
-
-                if device_auth.cipher_secret().is_err() {
-                    return Err(AddAccountError::CipherError);
+                //the caller decides what to do with the session it gave
+                let device_auth = self.create_device_auth(&account).await;
+                if exchanged {
+                    let _ = account.kill_session().await;
                 }
-                let descriptor = AccountDescriptor {
+                let (device_auth, superseded) = device_auth?;
+                let mut descriptor = AccountDescriptor {
                     display_name: account.display_name.clone().unwrap(),
                     device_auth: Some(device_auth),
+                    launch_args: String::new(),
+                    email: String::new(),
+                    last_verified: None,
+                    health: AccountHealth::Unknown,
                 };
+                descriptor.mark_verified();
 
                 self.insert_or_edit(&descriptor);
 
-                return Ok(descriptor);
+                return Ok((descriptor, superseded));
             }
         }
     }
@@ -249,8 +444,17 @@ impl Default for Configuration {
             accounts: Vec::new(),
             launcher: Launchers::EpicGamesLauncher,
             legendary_path:String::new(),
+            heroic_path:String::new(),
             version: crate::version::get_program_version().to_string(),
-            close_epic_games_launcher_on_swap: false
+            close_epic_games_launcher_on_swap: false,
+            restart_launcher_on_swap: false,
+            launcher_executable_path: String::new(),
+            game_path: String::new(),
+            endpoints: EpicEndpoints::default(),
+            http: HttpSettings::default(),
+            secret_store: SecretStoreKind::default(),
+            auto_lock_minutes: default_auto_lock_minutes(),
+            superseded_device_auths: Vec::new()
         }
     }
 }
@@ -11,6 +11,7 @@ use std::{
 use crate::{
     decrypt,
     epic::{EpicError, EpicErrorKind},
+    launchers::{is_process_running, spawn_executable, stop_process, Launcher, LauncherSession, Launchers},
 };
 
 const LAST_KNOWN_DECRYPTION_KEY: &'static str = "A09C853C9E95409BB94D707EADEFA52E";
@@ -83,7 +84,12 @@ pub fn epic_get_game_user_settings_path() -> std::io::Result<PathBuf> {
         }
     };
 
-    let game_user_settings_path = local_appdata.join("EpicGamesLauncher\\Saved\\Config\\Windows\\GameUserSettings.ini");
+    let game_user_settings_path = local_appdata
+        .join("EpicGamesLauncher")
+        .join("Saved")
+        .join("Config")
+        .join("Windows")
+        .join("GameUserSettings.ini");
 
     if !game_user_settings_path.exists() {
         return Err(std::io::Error::new(
@@ -250,3 +256,94 @@ pub fn epic_set_remember_me_data(entry: RememberMeEntry) -> Result<(), EpicError
 
     Ok(())
 }
+
+pub const EPIC_GAMES_LAUNCHER_PROCESS: &str = "EpicGamesLauncher.exe";
+
+//the launcher registers this protocol when it is installed
+#[cfg(windows)]
+const EPIC_GAMES_LAUNCHER_URI: &str = "com.epicgames.launcher://";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EpicGamesLauncher;
+
+impl Launcher for EpicGamesLauncher {
+    fn kind(&self) -> Launchers {
+        Launchers::EpicGamesLauncher
+    }
+
+    fn config_path(&self) -> Result<PathBuf, EpicError> {
+        epic_get_game_user_settings_path().map_err(|_| {
+            EpicError::new(
+                EpicErrorKind::NotFound,
+                Some("Failed to find GameUserSettings.ini path"),
+            )
+        })
+    }
+
+    fn read_active_account(&self) -> Result<RememberMeEntry, EpicError> {
+        epic_get_remember_me_data().map_err(|_| {
+            EpicError::new(
+                EpicErrorKind::Other,
+                Some("Failed to get EGL account to config"),
+            )
+        })
+    }
+
+    //the launcher only accepts launcherAppClient2 refresh tokens
+    fn write_active_account(&self, session: &LauncherSession) -> Result<(), EpicError> {
+        let refresh_token = session.account.refresh_token().ok_or(EpicError::new(
+            EpicErrorKind::Other,
+            Some("The session has no refresh token"),
+        ))?;
+
+        epic_set_remember_me_data(session.details.to_remember_me_entry(&refresh_token))
+    }
+
+    fn is_running(&self) -> bool {
+        is_process_running(EPIC_GAMES_LAUNCHER_PROCESS)
+    }
+
+    fn stop(&self) -> Result<Vec<u32>, EpicError> {
+        stop_process(EPIC_GAMES_LAUNCHER_PROCESS)
+    }
+
+    //default install folders, 64 bits first
+    fn executable_path(&self) -> Option<PathBuf> {
+        ["ProgramFiles(x86)", "ProgramFiles"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .flat_map(|program_files| {
+                ["Win64", "Win32"].map(|arch| {
+                    PathBuf::from(&program_files)
+                        .join("Epic Games")
+                        .join("Launcher")
+                        .join("Portal")
+                        .join("Binaries")
+                        .join(arch)
+                        .join(EPIC_GAMES_LAUNCHER_PROCESS)
+                })
+            })
+            .find(|path| path.exists())
+    }
+
+    fn start(&self, executable: Option<&Path>) -> Result<(), EpicError> {
+        if let Some(executable) = executable.map(PathBuf::from).or(self.executable_path()) {
+            return spawn_executable(&executable, &[]);
+        }
+
+        start_with_uri()
+    }
+}
+
+#[cfg(windows)]
+fn start_with_uri() -> Result<(), EpicError> {
+    spawn_executable(Path::new("cmd"), &["/C", "start", "", EPIC_GAMES_LAUNCHER_URI])
+}
+
+#[cfg(not(windows))]
+fn start_with_uri() -> Result<(), EpicError> {
+    Err(EpicError::new(
+        EpicErrorKind::NotFound,
+        Some("Failed to find EpicGamesLauncher.exe, set its path in the settings"),
+    ))
+}
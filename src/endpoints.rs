@@ -0,0 +1,156 @@
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+//overrides every service host at once, handy to point the app at a local mock server
+pub const BASE_URL_ENV_VAR: &str = "ALT_MANAGER_EPIC_BASE_URL";
+
+const ACCOUNT_SERVICE: &str = "https://account-public-service-prod.ol.epicgames.com";
+//device codes have always been requested from this instance of the account service
+const DEVICE_AUTHORIZATION_SERVICE: &str = "https://account-public-service-prod03.ol.epicgames.com";
+const EULA_TRACKING: &str = "https://eulatracking-public-service-prod-m.ol.epicgames.com";
+const FORTNITE_GATEWAY: &str = "https://fngw-mcp-gc-livefn.ol.epicgames.com";
+
+lazy_static! {
+    static ref ENDPOINTS: RwLock<EpicEndpoints> =
+        RwLock::new(EpicEndpoints::default().with_env_override());
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EpicEndpoints {
+    #[serde(default = "default_account_service")]
+    pub account_service: String,
+    #[serde(default = "default_device_authorization_service")]
+    pub device_authorization_service: String,
+    #[serde(default = "default_eula_tracking")]
+    pub eula_tracking: String,
+    #[serde(default = "default_fortnite_gateway")]
+    pub fortnite_gateway: String,
+}
+
+fn default_account_service() -> String {
+    ACCOUNT_SERVICE.to_string()
+}
+
+fn default_device_authorization_service() -> String {
+    DEVICE_AUTHORIZATION_SERVICE.to_string()
+}
+
+fn default_eula_tracking() -> String {
+    EULA_TRACKING.to_string()
+}
+
+fn default_fortnite_gateway() -> String {
+    FORTNITE_GATEWAY.to_string()
+}
+
+impl Default for EpicEndpoints {
+    fn default() -> Self {
+        Self {
+            account_service: default_account_service(),
+            device_authorization_service: default_device_authorization_service(),
+            eula_tracking: default_eula_tracking(),
+            fortnite_gateway: default_fortnite_gateway(),
+        }
+    }
+}
+
+impl EpicEndpoints {
+    //every service is served from the same base url
+    pub fn from_base_url(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/').to_string();
+
+        Self {
+            account_service: base_url.clone(),
+            device_authorization_service: base_url.clone(),
+            eula_tracking: base_url.clone(),
+            fortnite_gateway: base_url,
+        }
+    }
+
+    pub fn with_env_override(self) -> Self {
+        match std::env::var(BASE_URL_ENV_VAR) {
+            Ok(base_url) if !base_url.is_empty() => Self::from_base_url(&base_url),
+            _ => self,
+        }
+    }
+
+    pub fn token(&self) -> String {
+        format!("{}/account/api/oauth/token", self.account_service)
+    }
+
+    pub fn exchange(&self) -> String {
+        format!("{}/account/api/oauth/exchange", self.account_service)
+    }
+
+    pub fn kill_session(&self, access_token: &str) -> String {
+        format!("{}/account/api/oauth/sessions/kill/{}", self.account_service, access_token)
+    }
+
+    pub fn kill_sessions(&self, kill_type: &str) -> String {
+        format!("{}/account/api/oauth/sessions/kill?killType={}", self.account_service, kill_type)
+    }
+
+    pub fn device_authorization(&self) -> String {
+        format!("{}/account/api/oauth/deviceAuthorization", self.device_authorization_service)
+    }
+
+    pub fn account(&self, account_id: &str) -> String {
+        format!("{}/account/api/public/account/{}", self.account_service, account_id)
+    }
+
+    pub fn accept_privacy_policy(&self) -> String {
+        format!("{}/account/api/public/corrections/acceptPrivacyPolicy", self.account_service)
+    }
+
+    pub fn device_auth(&self, account_id: &str) -> String {
+        format!("{}/deviceAuth", self.account(account_id))
+    }
+
+    pub fn device_auth_entry(&self, account_id: &str, device_id: &str) -> String {
+        format!("{}/{}", self.device_auth(account_id), device_id)
+    }
+
+    pub fn eula_agreement(&self, account_id: &str) -> String {
+        format!(
+            "{}/eulatracking/api/public/agreements/fn/account/{}",
+            self.eula_tracking, account_id
+        )
+    }
+
+    pub fn eula_accept(&self, version: i64, account_id: &str, locale: &str) -> String {
+        format!(
+            "{}/eulatracking/api/public/agreements/fn/version/{}/account/{}/accept?locale={}",
+            self.eula_tracking, version, account_id, locale
+        )
+    }
+
+    pub fn grant_access(&self, account_id: &str) -> String {
+        format!(
+            "{}/fortnite/api/game/v2/grant_access/{}",
+            self.fortnite_gateway, account_id
+        )
+    }
+
+    pub fn user_files(&self, account_id: &str) -> String {
+        format!(
+            "{}/fortnite/api/cloudstorage/user/{}",
+            self.fortnite_gateway, account_id
+        )
+    }
+
+    pub fn user_file(&self, account_id: &str, unique_file_name: &str) -> String {
+        format!("{}/{}", self.user_files(account_id), unique_file_name)
+    }
+}
+
+pub fn endpoints() -> EpicEndpoints {
+    ENDPOINTS.read().map(|x| x.clone()).unwrap_or_default()
+}
+
+//env var always wins over the configuration so tests can't hit production by mistake
+pub fn set_endpoints(endpoints: EpicEndpoints) {
+    if let Ok(mut current) = ENDPOINTS.write() {
+        *current = endpoints.with_env_override();
+    }
+}
@@ -1,25 +1,30 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    egl::RememberMeEntry,
+    endpoints::endpoints,
+    epic_clients::AuthClient,
+    get_client,
+    health::AccountHealth,
+    http::{self, HttpResponse, SendWithRetries},
+    secret_store,
+};
 
-use crate::{egl::RememberMeEntry, epic_clients::AuthClient, get_client};
-
-use base64::{engine::general_purpose, Engine};
-use egui_toast::{Toast, ToastOptions};
-use lazy_static::lazy_static;
 use reqwest::StatusCode;
-use windows::{Win32::Security::Cryptography::{CryptProtectData, CRYPT_INTEGER_BLOB, CRYPTPROTECT_LOCAL_MACHINE, CryptUnprotectData}, core::PCWSTR};
+use zeroize::Zeroize;
 
-lazy_static! {
-    static ref CLIENT: reqwest::Client = reqwest::Client::new();
-}
+const DEVICE_AUTH_SECRET_KEY: u8 = 0x85;
 
-const TOKEN: &'static str =
-    "https://account-public-service-prod.ol.epicgames.com/account/api/oauth/token";
-const EXCHANGE: &'static str =
-    "https://account-public-service-prod.ol.epicgames.com/account/api/oauth/exchange";
-const GET_DEVICE_AUTHORIZATION: &'static str =
-    "https://account-public-service-prod03.ol.epicgames.com/account/api/oauth/deviceAuthorization";
+//killType of /oauth/sessions/kill keeping only the session making the request
+const KILL_OTHER_SESSIONS: &str = "OTHERS";
 
-const DEVICE_AUTH_SECRET_KEY: u8 = 0x85;
+//access tokens are refreshed a bit before epic would reject them
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
 
 #[derive(Default, Debug, Clone, PartialEq, serde::Deserialize)]
 pub struct FileEntry {
@@ -59,65 +64,61 @@ pub struct DeviceAuth {
     pub secret: String,
 }
 
+//where and when a device auth was created or last used
+#[derive(Default, Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceAuthLocation {
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub ip_address: Option<String>,
+    #[serde(default)]
+    pub date_time: Option<String>,
+}
+
+//a device auth as listed by epic, without its secret
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceAuthInfo {
+    pub device_id: String,
+    pub account_id: String,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub created: Option<DeviceAuthLocation>,
+    #[serde(default)]
+    pub last_access: Option<DeviceAuthLocation>,
+}
+
+//the secret may be unciphered, don't leave it behind in memory
+impl Drop for DeviceAuth {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
 impl DeviceAuth {
-    //TODO : change the cipher algorithm with dpapi or AES
-    pub fn cipher_secret(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let in_blob = CRYPT_INTEGER_BLOB {
-            cbData: self.secret.len() as u32,
-            pbData: self.secret.as_ptr() as *mut u8,
-        };
-        
-        let mut out_blob = unsafe { std::mem::zeroed::<CRYPT_INTEGER_BLOB>() };
-
-        unsafe {
-            CryptProtectData(
-                &in_blob,
-                PCWSTR::null(),
-                None,
-                None,
-                None,
-                CRYPTPROTECT_LOCAL_MACHINE,
-                &mut out_blob,
+    //seals the plain secret with the active secret store
+    pub fn cipher_secret(&mut self) -> Result<(), EpicError> {
+        self.secret = secret_store::seal(self.secret.as_bytes()).map_err(|error| {
+            EpicError::new(
+                EpicErrorKind::CipherError,
+                Some(format!("Failed to cipher secret key : {}", error)),
             )
-        }?;
+        })?;
 
-        self.secret = general_purpose::STANDARD.encode(unsafe { 
-            std::slice::from_raw_parts(out_blob.pbData, out_blob.cbData as usize)
-        });
-        println!("Secret : {}", self.secret);
         Ok(())
     }
 
     pub fn uncipher_secret(&mut self) -> Result<(), EpicError> {
-        let raw_data = general_purpose::STANDARD.decode(self.secret.as_bytes()).map_err(|_| {
+        let secret = secret_store::unseal(&self.secret).map_err(|error| {
             EpicError::new(
                 EpicErrorKind::CipherError,
-                Some("Failed to uncipher secret key"),
+                Some(format!("Failed to uncipher secret key : {}", error)),
             )
         })?;
 
-        let in_blob = CRYPT_INTEGER_BLOB {
-            cbData: raw_data.len() as u32,
-            pbData: raw_data.as_ptr() as *mut u8,
-        };
-
-        let mut out_blob = unsafe { std::mem::zeroed::<CRYPT_INTEGER_BLOB>() };
-
-        unsafe {
-            CryptUnprotectData(
-                &in_blob,
-                None,
-                None,
-                None,
-                None,
-                CRYPTPROTECT_LOCAL_MACHINE,
-                &mut out_blob,
-            )
-        }.map_err(|_| EpicError::new(EpicErrorKind::CipherError, Some("Failed to uncipher secret key")))?;
-
-        self.secret = String::from_utf8(unsafe {
-            Vec::from_raw_parts(out_blob.pbData, out_blob.cbData as usize, out_blob.cbData as usize)
-        }).map_err(|_| EpicError::new(EpicErrorKind::CipherError, Some("Failed to decode secret key")))?;
+        self.secret = String::from_utf8(secret.to_vec()).map_err(|_| EpicError::new(EpicErrorKind::CipherError, Some("Failed to decode secret key")))?;
 
         Ok(())
     }
@@ -133,6 +134,7 @@ impl DeviceAuth {
     }
 
     pub async fn login(&mut self) -> Result<EpicAccount, EpicError> {
+        let sealed = self.clone();
         self.uncipher_secret().map_err(|_| {
             EpicError::new(
                 EpicErrorKind::CipherError,
@@ -142,7 +144,7 @@ impl DeviceAuth {
         let response =
             crate::epic::token(Token::DeviceAuth(&self), get_client!("fortniteIOSGameClient")).await?;
 
-        Ok(response)
+        Ok(response.with_device_auth(sealed))
     }
 }
 
@@ -150,15 +152,53 @@ impl DeviceAuth {
 pub struct AccountDescriptor {
     pub display_name: String,
     pub device_auth: Option<DeviceAuth>,
+    //appended to the game command line by "Play as"
+    #[serde(default)]
+    pub launch_args: String,
+    #[serde(default)]
+    pub email: String,
+    //unix timestamp of the last login with the device auth
+    #[serde(default)]
+    pub last_verified: Option<u64>,
+    #[serde(default)]
+    pub health: AccountHealth,
 }
 
+//display names can be changed on epic's side, the account id can't
 impl PartialEq for AccountDescriptor {
     fn eq(&self, other: &Self) -> bool {
-        self.display_name == other.display_name
+        match (self.account_id(), other.account_id()) {
+            (Some(account_id), Some(other_account_id)) => account_id == other_account_id,
+            _ => self.display_name == other.display_name,
+        }
     }
 }
 
 impl AccountDescriptor {
+    pub fn account_id(&self) -> Option<&str> {
+        self.device_auth.as_ref().map(|x| x.account_id.as_str())
+    }
+
+    pub fn mark_verified(&mut self) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        self.last_verified = Some(now.as_secs());
+        self.health = AccountHealth::Healthy;
+    }
+
+    //returns true if epic knows the account under another name or email
+    pub fn update_profile(&mut self, details: &EpicAccountDetails) -> bool {
+        let changed = self.display_name != details.display_name || self.email != details.email;
+
+        self.display_name = details.display_name.clone();
+        self.email = details.email.clone();
+        self.mark_verified();
+
+        changed
+    }
+
     // pub fn is_currently_used(&self) -> bool {
     //     match epic_get_remember_me_data() {
     //         Ok(data) => {
@@ -181,8 +221,10 @@ impl AccountDescriptor {
 
         device_auth.uncipher_secret()?;
         let account = token(Token::DeviceAuth(&device_auth), get_client!("fortniteIOSGameClient")).await?;
-        let account = account.exchange_to(get_client!("launcherAppClient2")).await?;
-        Ok(account)
+        let launcher_account = account.exchange_to(get_client!("launcherAppClient2")).await;
+        let _ = account.kill_session().await;
+
+        launcher_account
     }
 }
 
@@ -231,24 +273,182 @@ pub enum EpicEula {
     None
 }
 
+//tokens of a session, replaced each time it is refreshed
 #[derive(serde::Deserialize, Clone, Debug)]
-pub struct EpicAccount {
+pub struct EpicTokens {
     pub access_token: String,
     pub refresh_token: Option<String>,
+    pub expires_in: Option<i64>,
+    pub expires_at: Option<String>,
+    pub refresh_expires: Option<i64>,
+    pub refresh_expires_at: Option<String>,
+    //expires_in and refresh_expires count from the response
+    #[serde(skip, default = "Instant::now")]
+    received_at: Instant,
+}
+
+impl EpicTokens {
+    fn time_left(&self, seconds: Option<i64>) -> Option<Duration> {
+        let lifetime = Duration::from_secs(seconds?.max(0) as u64);
+
+        Some((self.received_at + lifetime).saturating_duration_since(Instant::now()))
+    }
+
+    pub fn expires_soon(&self) -> bool {
+        self.time_left(self.expires_in)
+            .is_some_and(|x| x <= TOKEN_REFRESH_MARGIN)
+    }
+
+    pub fn can_refresh(&self) -> bool {
+        self.refresh_token.is_some() && !self.time_left(self.refresh_expires).is_some_and(|x| x.is_zero())
+    }
+}
+
+fn shared_tokens<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Arc<RwLock<EpicTokens>>, D::Error> {
+    <EpicTokens as serde::Deserialize>::deserialize(deserializer).map(|tokens| Arc::new(RwLock::new(tokens)))
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct EpicAccount {
     #[serde(rename = "displayName")]
     pub display_name: Option<String>,
     pub account_id: Option<String>,
     pub client_id: String,
+    //shared by the clones so none of them keeps a refresh token epic already swapped
+    #[serde(flatten, deserialize_with = "shared_tokens")]
+    tokens: Arc<RwLock<EpicTokens>>,
+    #[serde(skip)]
+    refreshing: Arc<tokio::sync::Mutex<()>>,
+    //still ciphered, logs in again once the refresh token is gone
+    #[serde(skip)]
+    device_auth: Option<DeviceAuth>,
 }
 
 impl EpicAccount {
-    pub async fn get_eula_data(&self) -> Result<EpicEula, EpicError> {
-        let response = CLIENT
-            .get(format!("https://eulatracking-public-service-prod-m.ol.epicgames.com/eulatracking/api/public/agreements/fn/account/{}", self.account_id.clone().unwrap()))
-            .bearer_auth(&self.access_token)
-            .send()
+    pub fn tokens(&self) -> EpicTokens {
+        self.tokens.read().unwrap().clone()
+    }
+
+    pub fn access_token(&self) -> String {
+        self.tokens().access_token
+    }
+
+    pub fn refresh_token(&self) -> Option<String> {
+        self.tokens().refresh_token
+    }
+
+    pub fn with_device_auth(mut self, device_auth: DeviceAuth) -> Self {
+        self.device_auth = Some(device_auth);
+        self
+    }
+
+    fn can_renew(&self) -> bool {
+        self.tokens().can_refresh() || self.device_auth.is_some()
+    }
+
+    //swaps the refresh token, or logs in again with the device auth when it is expired or revoked
+    async fn renew(&self) -> Result<EpicAccount, EpicError> {
+        let tokens = self.tokens();
+        let client = AuthClient::by_id(&self.client_id);
+
+        let refreshed = match (tokens.refresh_token.as_deref(), client) {
+            (Some(refresh_token), Some(client)) if tokens.can_refresh() => {
+                Some(token(Token::RefreshToken(refresh_token), client).await)
+            }
+            _ => None,
+        };
+
+        match (refreshed, self.device_auth.clone()) {
+            (Some(Ok(account)), _) => Ok(account),
+            (_, Some(mut device_auth)) => device_auth.login().await,
+            (Some(Err(error)), None) => Err(error),
+            (None, None) => Err(EpicError::new(
+                EpicErrorKind::Other,
+                Some("The session expired and can't be refreshed"),
+            )),
+        }
+    }
+
+    //the access token that was used tells if another request already refreshed the session
+    async fn refresh(&self, used_access_token: &str) -> Result<(), EpicError> {
+        let _refreshing = self.refreshing.lock().await;
+
+        if self.access_token() != used_access_token {
+            return Ok(());
+        }
+
+        let tokens = self.renew().await?.tokens();
+        *self.tokens.write().unwrap() = tokens;
+
+        Ok(())
+    }
+
+    //refreshes the session when it is about to expire and once more when epic rejects the token anyway
+    async fn send_authorized(
+        &self,
+        request: impl Fn(reqwest::Client) -> reqwest::RequestBuilder,
+    ) -> Result<HttpResponse, EpicError> {
+        let mut tokens = self.tokens();
+
+        if tokens.expires_soon() && self.can_renew() {
+            self.refresh(&tokens.access_token).await?;
+            tokens = self.tokens();
+        }
+
+        let response = request(http::client())
+            .bearer_auth(&tokens.access_token)
+            .send_with_retries()
+            .await?;
+
+        if response.status() != StatusCode::UNAUTHORIZED || !self.can_renew() {
+            return Ok(response);
+        }
+
+        //the refresh needs a permit of its own
+        drop(response);
+        self.refresh(&tokens.access_token).await?;
+
+        request(http::client())
+            .bearer_auth(self.access_token())
+            .send_with_retries()
             .await
-            .map_err(|_| EpicError::reqwest_internal_error())?;
+    }
+
+    //ends this session, its refresh token stops working too
+    pub async fn kill_session(&self) -> Result<(), EpicError> {
+        let access_token = self.access_token();
+        let response = http::client()
+            .delete(endpoints().kill_session(&access_token))
+            .bearer_auth(&access_token)
+            .send_with_retries()
+            .await?;
+
+        //an expired session is already gone
+        if response.status().is_success() || response.status() == StatusCode::UNAUTHORIZED {
+            return Ok(());
+        }
+
+        Err(EpicError::from_response(response).await)
+    }
+
+    //logs the account out of every launcher, game and device but this session
+    pub async fn kill_other_sessions(&self) -> Result<(), EpicError> {
+        let response = self
+            .send_authorized(|client| client.delete(endpoints().kill_sessions(KILL_OTHER_SESSIONS)))
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(EpicError::from_response(response).await);
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_eula_data(&self) -> Result<EpicEula, EpicError> {
+        let account_id = self.account_id.clone().unwrap();
+        let response = self
+            .send_authorized(|client| client.get(endpoints().eula_agreement(&account_id)))
+            .await?;
 
         match response.status() {
             StatusCode::OK => {
@@ -260,7 +460,7 @@ impl EpicAccount {
                 return Ok(EpicEula::Accepted)
             },
             _ => {
-                return Err(EpicError::reqwest_error(response.status()));
+                return Err(EpicError::from_response(response).await);
             }
         }
     }
@@ -275,15 +475,11 @@ impl EpicAccount {
                 return Ok(());
             },
             EpicEula::Pending(version, locale) => {
-                let response = CLIENT
-                    .post(format!("https://eulatracking-public-service-prod-m.ol.epicgames.com/eulatracking/api/public/agreements/fn/version/{}/account/{}/accept?locale={}", version, self.account_id.clone().unwrap(), locale))
-                    .bearer_auth(&self.access_token)
-                    .send()
-                    .await
-                    .map_err(|_| EpicError::reqwest_internal_error())?;
+                let url = endpoints().eula_accept(version, &self.account_id.clone().unwrap(), &locale);
+                let response = self.send_authorized(|client| client.post(&url)).await?;
 
                 if !response.status().is_success() {
-                    return Err(EpicError::reqwest_error(response.status()));
+                    return Err(EpicError::from_response(response).await);
                 }
 
                 return Ok(());
@@ -297,15 +493,11 @@ impl EpicAccount {
     //grant account access to Fortnite using EpicGames api
     pub async fn grant_access(&self) -> EpicResult<()> 
     {
-        let response = CLIENT
-            .post(format!("https://fngw-mcp-gc-livefn.ol.epicgames.com/fortnite/api/game/v2/grant_access/{}", self.account_id.clone().unwrap()))
-            .bearer_auth(&self.access_token)
-            .send()
-            .await
-            .map_err(|_| EpicError::reqwest_internal_error())?;
+        let url = endpoints().grant_access(&self.account_id.clone().unwrap());
+        let response = self.send_authorized(|client| client.post(&url)).await?;
 
         if !response.status().is_success() {
-            return Err(EpicError::reqwest_error(response.status()));
+            return Err(EpicError::from_response(response).await);
         }
 
         return Ok(());
@@ -317,12 +509,9 @@ impl EpicAccount {
         let mut body = HashMap::new();
         body.insert("prompt", "login");
 
-        let response = CLIENT
-            .post(GET_DEVICE_AUTHORIZATION)
-            .form(&body)
-            .bearer_auth(&self.access_token)
-            .send()
-            .await.map_err(|_| EpicError::reqwest_internal_error())?;
+        let response = self
+            .send_authorized(|client| client.post(endpoints().device_authorization()).form(&body))
+            .await?;
 
         if !response.status().is_success() {
             // if cfg!(debug_assertions) {
@@ -330,7 +519,7 @@ impl EpicAccount {
             // eprintln!("Body : {}", response.text().await?);
             // }
             
-            return Err(EpicError::reqwest_error(response.status()));
+            return Err(EpicError::from_response(response).await);
         }
 
         let authorization = response.json::<DeviceAuthorization>().await.map_err(|_| EpicError::new(EpicErrorKind::ParsingError, Some("Failed to parse JSON data")))?;
@@ -339,17 +528,8 @@ impl EpicAccount {
     }
 
     pub async fn get_user_files(&self) -> Result<Vec<FileEntry>, EpicError> {
-        let response = CLIENT
-            .get(format!(
-                "https://fngw-mcp-gc-livefn.ol.epicgames.com/fortnite/api/cloudstorage/user/{}",
-                self.account_id.clone().unwrap()
-            ))
-            .bearer_auth(&self.access_token)
-            .send()
-            .await
-            .map_err(|_| {
-                EpicError::new(EpicErrorKind::HttpError, Some("Reqwest internal error"))
-            })?;
+        let url = endpoints().user_files(&self.account_id.clone().unwrap());
+        let response = self.send_authorized(|client| client.get(&url)).await?;
 
         let status = response.status();
 
@@ -360,24 +540,19 @@ impl EpicAccount {
         } else {
             //     println!("Response : {}", response.text().await.unwrap());
             eprintln!("error while getting user files");
-            return Err(EpicError::reqwest_error(status));
+            return Err(EpicError::from_response(response).await);
         }
     }
 
     pub async fn create_file(&self, unique_file_name: impl Into<String>, data:Vec<u8>) -> Result<(), EpicError> {
-        let response = CLIENT
-        .put(format!("https://fngw-mcp-gc-livefn.ol.epicgames.com/fortnite/api/cloudstorage/user/{}/{}",self.account_id.clone().unwrap(), unique_file_name.into()))
-        .bearer_auth(&self.access_token)
-        .body(data)
-        .send()
-        .await.map_err(|_| EpicError::reqwest_internal_error())?;
+        let url = endpoints().user_file(&self.account_id.clone().unwrap(), &unique_file_name.into());
+        let response = self.send_authorized(|client| client.put(&url).body(data.clone())).await?;
 
         if response.status().is_success() {
             return Ok(());
         } else {
-            let status = response.status();
-            eprintln!("error while creating file, body : {}", response.text().await.unwrap());
-            return Err(EpicError::reqwest_error(status));
+            eprintln!("error while creating file");
+            return Err(EpicError::from_response(response).await);
         }
     }
 
@@ -386,22 +561,13 @@ impl EpicAccount {
         unique_file_name: impl Into<String>,
         data: Vec<u8>,
     ) -> Result<(), EpicError> {
-        let response = CLIENT
-            .put(format!(
-                "https://fngw-mcp-gc-livefn.ol.epicgames.com/fortnite/api/cloudstorage/user/{}/{}",
-                self.account_id.clone().unwrap(),
-                unique_file_name.into()
-            ))
-            .bearer_auth(&self.access_token)
-            .body(data)
-            .send()
-            .await
-            .map_err(|_| EpicError::reqwest_internal_error())?;
+        let url = endpoints().user_file(&self.account_id.clone().unwrap(), &unique_file_name.into());
+        let response = self.send_authorized(|client| client.put(&url).body(data.clone())).await?;
 
         if response.status().is_success() {
             return Ok(());
         } else {
-            return Err(EpicError::reqwest_error(response.status()));
+            return Err(EpicError::from_response(response).await);
         }
     }
 
@@ -432,43 +598,27 @@ impl EpicAccount {
         &self,
         unique_file_name: impl Into<String>,
     ) -> Result<Vec<u8>, EpicError> {
-        let response = CLIENT
-            .get(format!(
-                "https://fngw-mcp-gc-livefn.ol.epicgames.com/fortnite/api/cloudstorage/user/{}/{}",
-                self.account_id.clone().unwrap(),
-                unique_file_name.into()
-            ))
-            .bearer_auth(&self.access_token)
-            .send()
-            .await
-            .map_err(|_| EpicError::reqwest_internal_error())?;
+        let url = endpoints().user_file(&self.account_id.clone().unwrap(), &unique_file_name.into());
+        let response = self.send_authorized(|client| client.get(&url)).await?;
 
         if response.status().is_success() {
             let bytes = response.bytes().await.map_err(|_| {
                 EpicError::new(EpicErrorKind::ParsingError, Some("Failed to parse bytes"))
             })?;
-            return Ok(bytes.to_vec());
+            return Ok(bytes);
         } else {
-            println!("error while getting file");
-            return Err(EpicError::reqwest_error(response.status()));
+            eprintln!("error while getting file");
+            return Err(EpicError::from_response(response).await);
         }
     }
 
     pub async fn get_infos(&self) -> Result<EpicAccountDetails, EpicError> {
-        let url = format!(
-            "https://account-public-service-prod.ol.epicgames.com/account/api/public/account/{}",
-            self.account_id.clone().unwrap_or(String::from("0"))
-        );
-
-        let response = CLIENT
-            .get(url)
-            .bearer_auth(self.access_token.clone())
-            .send()
-            .await
-            .map_err(|_| EpicError::reqwest_internal_error())?;
+        let url = endpoints().account(&self.account_id.clone().unwrap_or(String::from("0")));
+
+        let response = self.send_authorized(|client| client.get(&url)).await?;
 
         if !response.status().is_success() {
-            return Err(EpicError::reqwest_error(response.status()));
+            return Err(EpicError::from_response(response).await);
         }
 
         Ok(response.json::<EpicAccountDetails>().await.map_err(|_| {
@@ -480,15 +630,10 @@ impl EpicAccount {
     }
 
     pub async fn exchange_code(&self) -> Result<String, EpicError> {
-        let response = CLIENT
-            .get(EXCHANGE)
-            .bearer_auth(self.access_token.clone())
-            .send()
-            .await
-            .map_err(|_| EpicError::reqwest_internal_error())?;
+        let response = self.send_authorized(|client| client.get(endpoints().exchange())).await?;
 
         if !response.status().is_success() {
-            return Err(EpicError::reqwest_error(response.status()));
+            return Err(EpicError::from_response(response).await);
         }
 
         let data = response.json::<ExchangeCode>().await.map_err(|_| {
@@ -509,17 +654,40 @@ impl EpicAccount {
         Ok(account)
     }
 
+    pub async fn get_device_auths(&self) -> Result<Vec<DeviceAuthInfo>, EpicError> {
+        let url = endpoints().device_auth(&self.account_id.clone().unwrap());
+        let response = self.send_authorized(|client| client.get(&url)).await?;
+
+        if !response.status().is_success() {
+            return Err(EpicError::from_response(response).await);
+        }
+
+        response.json::<Vec<DeviceAuthInfo>>().await.map_err(|_| {
+            EpicError::new(
+                EpicErrorKind::ParsingError,
+                Some("Failed to parse JSON data"),
+            )
+        })
+    }
+
+    pub async fn delete_device_auth(&self, device_id: &str) -> Result<(), EpicError> {
+        let url = endpoints().device_auth_entry(&self.account_id.clone().unwrap(), device_id);
+        let response = self.send_authorized(|client| client.delete(&url)).await?;
+
+        if !response.status().is_success() {
+            return Err(EpicError::from_response(response).await);
+        }
+
+        Ok(())
+    }
+
     pub async fn create_device_auth(&self) -> Result<DeviceAuth, Box<dyn std::error::Error>> {
-        let url = format!("https://account-public-service-prod.ol.epicgames.com/account/api/public/account/{}/deviceAuth", self.account_id.clone().unwrap());
+        let url = endpoints().device_auth(&self.account_id.clone().unwrap());
 
-        let response = CLIENT
-            .post(url)
-            .bearer_auth(self.access_token.clone())
-            .send()
-            .await?;
+        let response = self.send_authorized(|client| client.post(&url)).await?;
 
         if !response.status().is_success() {
-            return Err("Failed to create device auth".into());
+            return Err(Box::new(EpicError::from_response(response).await));
         }
 
         Ok(response.json::<DeviceAuth>().await?)
@@ -564,9 +732,9 @@ impl Display for TokenType {
     }
 }
 
+#[derive(Clone, Copy)]
 pub enum Token<'a> {
     RefreshToken(&'a str),
-    #[allow(dead_code)]
     AuthorizationCode(&'a str),
     ExchangeCode(&'a str),
     ClientCredentials,
@@ -574,6 +742,98 @@ pub enum Token<'a> {
     DeviceCode(&'a str),
 }
 
+pub mod error_codes {
+    pub const INVALID_GRANT: &str = "errors.com.epicgames.account.oauth.invalid_grant";
+    pub const INVALID_ACCOUNT_CREDENTIALS: &str = "errors.com.epicgames.account.invalid_account_credentials";
+    pub const ACCOUNT_NOT_ACTIVE: &str = "errors.com.epicgames.account.account_not_active";
+    pub const CORRECTIVE_ACTION_REQUIRED: &str = "errors.com.epicgames.oauth.corrective_action_required";
+    pub const AUTHENTICATION_FAILED: &str = "errors.com.epicgames.common.authentication.authentication_failed";
+    pub const THROTTLED: &str = "errors.com.epicgames.common.throttled";
+    pub const DEVICE_AUTH_NOT_FOUND: &str = "errors.com.epicgames.account.device_auth.not_found";
+}
+
+//the json body epic sends with a failed request
+#[derive(Default, Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpicApiError {
+    #[serde(skip)]
+    pub status: u16,
+    pub error_code: String,
+    #[serde(default)]
+    pub error_message: String,
+    #[serde(default)]
+    pub numeric_error_code: i64,
+    #[serde(default)]
+    pub message_vars: Vec<String>,
+    //only sent with corrective_action_required
+    #[serde(default)]
+    pub corrective_action: Option<String>,
+    #[serde(default)]
+    pub continuation: Option<String>,
+}
+
+//something epic wants done on the account before it can log in again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorrectiveAction {
+    PrivacyPolicyAcceptance,
+    DateOfBirth,
+    DisplayNameUpdate,
+    Other(String),
+}
+
+impl CorrectiveAction {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "PRIVACY_POLICY_ACCEPTANCE" => CorrectiveAction::PrivacyPolicyAcceptance,
+            "DATE_OF_BIRTH" => CorrectiveAction::DateOfBirth,
+            "DISPLAY_NAME_UPDATE" => CorrectiveAction::DisplayNameUpdate,
+            other => CorrectiveAction::Other(other.to_string()),
+        }
+    }
+
+    //the other ones need the user on epicgames.com
+    pub fn is_supported(&self) -> bool {
+        matches!(self, CorrectiveAction::PrivacyPolicyAcceptance)
+    }
+}
+
+impl Display for CorrectiveAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CorrectiveAction::PrivacyPolicyAcceptance => write!(f, "accepting the updated privacy policy"),
+            CorrectiveAction::DateOfBirth => write!(f, "entering a date of birth"),
+            CorrectiveAction::DisplayNameUpdate => write!(f, "choosing a new display name"),
+            CorrectiveAction::Other(action) => write!(f, "{}", action),
+        }
+    }
+}
+
+impl EpicApiError {
+    //the messages of epic are written for developers, the common ones are explained
+    pub fn describe(&self) -> String {
+        match self.error_code.as_str() {
+            error_codes::INVALID_GRANT | error_codes::INVALID_ACCOUNT_CREDENTIALS => {
+                "The login was refused, the credentials expired or were revoked".to_string()
+            }
+            error_codes::ACCOUNT_NOT_ACTIVE => "The account is banned or disabled, check it on epicgames.com".to_string(),
+            error_codes::CORRECTIVE_ACTION_REQUIRED => match self.corrective_action.as_deref().map(CorrectiveAction::parse) {
+                Some(action) if action.is_supported() => {
+                    format!("Epic requires {} on this account, complete it from the account menu", action)
+                }
+                Some(action) => format!("Epic requires {} on this account, log in on epicgames.com to complete it", action),
+                None => "Epic requires an action on this account, log in on epicgames.com to complete it".to_string(),
+            },
+            error_codes::AUTHENTICATION_FAILED => "The session expired, try again".to_string(),
+            error_codes::THROTTLED => match self.message_vars.first() {
+                Some(seconds) => format!("Too many requests, try again in {} seconds", seconds),
+                None => "Too many requests, try again later".to_string(),
+            },
+            _ if !self.error_message.is_empty() => self.error_message.clone(),
+            _ => format!("Invalid API Response, got response {}", self.status),
+        }
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum EpicErrorKind {
@@ -585,6 +845,8 @@ pub enum EpicErrorKind {
     Authentification,
     ParsingError,
     InvalidResponse,
+    //epic answered with an error envelope
+    Api(Box<EpicApiError>),
     Other,
 }
 
@@ -599,6 +861,7 @@ impl Into<String> for EpicErrorKind {
             EpicErrorKind::NotFound => "NotFound".to_string(),
             EpicErrorKind::ParsingError => "ParsingError".to_string(),
             EpicErrorKind::InvalidResponse => "InvalidResponse".to_string(),
+            EpicErrorKind::Api(_) => "Api".to_string(),
             EpicErrorKind::Other => "Other".to_string(),
         }
     }
@@ -624,14 +887,6 @@ impl EpicError {
         }
     }
 
-    pub fn to_toast(&self) -> Toast {
-        Toast {
-            kind: egui_toast::ToastKind::Error,
-            text: egui::WidgetText::RichText(self.to_string().into()),
-            options: ToastOptions::default().duration_in_seconds(10.),
-        }
-    }
-
     pub fn reqwest_error(status: StatusCode) -> Self {
         return Self {
             kind: EpicErrorKind::InvalidResponse,
@@ -639,6 +894,54 @@ impl EpicError {
         };
     }
 
+    //the status alone does not tell why a request failed, the body does
+    pub async fn from_response(response: HttpResponse) -> Self {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+
+        if cfg!(debug_assertions) {
+            eprintln!("Error Body : {}", body);
+        }
+
+        Self::response_error(status, &body)
+    }
+
+    pub fn response_error(status: StatusCode, body: &str) -> Self {
+        match serde_json::from_str::<EpicApiError>(body) {
+            Ok(mut api_error) => {
+                api_error.status = status.as_u16();
+
+                Self {
+                    message: Some(api_error.describe()),
+                    kind: EpicErrorKind::Api(Box::new(api_error)),
+                }
+            }
+            Err(_) => Self::reqwest_error(status),
+        }
+    }
+
+    pub fn kind(&self) -> &EpicErrorKind {
+        &self.kind
+    }
+
+    pub fn error_code(&self) -> Option<&str> {
+        match &self.kind {
+            EpicErrorKind::Api(api_error) => Some(&api_error.error_code),
+            _ => None,
+        }
+    }
+
+    //the action and the continuation token needed to complete it
+    pub fn corrective_action(&self) -> Option<(CorrectiveAction, &str)> {
+        match &self.kind {
+            EpicErrorKind::Api(api_error) if api_error.error_code == error_codes::CORRECTIVE_ACTION_REQUIRED => Some((
+                CorrectiveAction::parse(api_error.corrective_action.as_deref()?),
+                api_error.continuation.as_deref()?,
+            )),
+            _ => None,
+        }
+    }
+
     pub fn reqwest_internal_error() -> Self {
         return Self {
             kind: EpicErrorKind::HttpError,
@@ -649,6 +952,10 @@ impl EpicError {
 
 impl Display for EpicError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let EpicErrorKind::Api(api_error) = &self.kind {
+            return write!(f, "{} ({})", api_error.describe(), api_error.error_code);
+        }
+
         let data = self.clone();
 
         write!(
@@ -704,24 +1011,20 @@ pub async fn token<'a>(
         }
     }
 
-    let response = CLIENT
-        .post(TOKEN)
+    let response = http::client()
+        .post(endpoints().token())
         .form(&params)
         .basic_auth(client.id, Some(client.secret))
-        .send()
-        .await
-        .map_err(|_| EpicError::reqwest_internal_error())?;
+        .send_with_retries()
+        .await?;
 
     let status = response.status();
 
     if !status.is_success() {
         if cfg!(debug_assertions) {
             eprintln!("Error URL : {}", response.url().to_string());
-            if let Ok(body) = response.text().await {
-                eprintln!("Error Body : {}", body);
-            }
         }
-        return Err(EpicError::reqwest_error(status));
+        return Err(EpicError::from_response(response).await);
     }
 
     let data: EpicAccount = response.json().await.map_err(|_| {
@@ -733,3 +1036,49 @@ pub async fn token<'a>(
 
     Ok(data)
 }
+
+pub async fn complete_corrective_action(action: &CorrectiveAction, continuation: &str) -> Result<(), EpicError> {
+    let url = match action {
+        CorrectiveAction::PrivacyPolicyAcceptance => endpoints().accept_privacy_policy(),
+        action => {
+            return Err(EpicError::new(
+                EpicErrorKind::Other,
+                Some(format!("Epic requires {}, it can only be done on epicgames.com", action)),
+            ))
+        }
+    };
+
+    let mut body = HashMap::new();
+    body.insert("continuation", continuation);
+
+    let response = http::client()
+        .put(url)
+        .json(&body)
+        .send_with_retries()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(EpicError::from_response(response).await);
+    }
+
+    Ok(())
+}
+
+//completes the supported corrective actions and tries once more, like accept_eula does for the game
+pub async fn token_with_corrective_actions<'a>(
+    token: Token<'a>,
+    client: AuthClient<'a>,
+) -> Result<EpicAccount, EpicError> {
+    let error = match self::token(token, client).await {
+        Ok(account) => return Ok(account),
+        Err(error) => error,
+    };
+
+    match error.corrective_action() {
+        Some((action, continuation)) if action.is_supported() => {
+            complete_corrective_action(&action, continuation).await?;
+            self::token(token, client).await
+        }
+        _ => Err(error),
+    }
+}
@@ -35,4 +35,9 @@ impl AuthClient<'_> {
     pub fn get(name:&str) -> Option<AuthClient<'static>> {
         AuthClient::clients().into_iter().find(|client| client.name == name)
     }
+
+    //a session only knows the id of the client it was created with
+    pub fn by_id(id: &str) -> Option<AuthClient<'static>> {
+        AuthClient::clients().into_iter().find(|client| client.id == id)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,247 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Child,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    config::Configuration,
+    epic::{EpicError, EpicErrorKind},
+    heroic::Heroic,
+    legendary::Legendary,
+    process::{self, ProcessInfo},
+};
+
+pub const FORTNITE_APP_NAME: &str = "Fortnite";
+pub const FORTNITE_EXECUTABLE: &str = "FortniteLauncher.exe";
+
+//what the launchers pass to the game, the exchange code replaces the launcher session
+pub fn fortnite_arguments(exchange_code: &str, account_id: &str, display_name: &str, extra_arguments: &str) -> Vec<String> {
+    let mut arguments = vec![
+        "-AUTH_LOGIN=unused".to_string(),
+        format!("-AUTH_PASSWORD={}", exchange_code),
+        "-AUTH_TYPE=exchangecode".to_string(),
+        format!("-epicapp={}", FORTNITE_APP_NAME),
+        "-epicenv=Prod".to_string(),
+        "-EpicPortal".to_string(),
+        format!("-epicusername={}", display_name),
+        format!("-epicuserid={}", account_id),
+        "-epiclocale=en-us".to_string(),
+    ];
+
+    arguments.extend(split_arguments(extra_arguments));
+    arguments
+}
+
+//splits on whitespace, double quotes keep spaces inside an argument
+pub fn split_arguments(arguments: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut started = false;
+
+    for character in arguments.chars() {
+        match character {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            x if x.is_whitespace() && !quoted => {
+                if started {
+                    result.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            x => {
+                current.push(x);
+                started = true;
+            }
+        }
+    }
+
+    if started {
+        result.push(current);
+    }
+
+    result
+}
+
+//the game path can be the install folder or the executable itself
+pub fn executable_from_path(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        return path
+            .join("FortniteGame")
+            .join("Binaries")
+            .join("Win64")
+            .join(FORTNITE_EXECUTABLE);
+    }
+
+    path.to_path_buf()
+}
+
+#[derive(serde::Deserialize)]
+struct EglManifest {
+    #[serde(rename = "AppName")]
+    app_name: String,
+    #[serde(rename = "InstallLocation")]
+    install_location: PathBuf,
+}
+
+fn egl_install_path() -> Option<PathBuf> {
+    let manifests = PathBuf::from(std::env::var("ProgramData").ok()?)
+        .join("Epic")
+        .join("EpicGamesLauncher")
+        .join("Data")
+        .join("Manifests");
+
+    std::fs::read_dir(manifests)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|x| x == "item"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|data| serde_json::from_str::<EglManifest>(&data).ok())
+        .find(|manifest| manifest.app_name == FORTNITE_APP_NAME)
+        .map(|manifest| manifest.install_location)
+}
+
+#[derive(serde::Deserialize)]
+struct LegendaryInstall {
+    install_path: PathBuf,
+}
+
+//legendary and heroic list their games in installed.json, next to user.json
+pub fn legendary_install_path(legendary_folder: &Path) -> Option<PathBuf> {
+    let data = std::fs::read_to_string(legendary_folder.join("installed.json")).ok()?;
+
+    serde_json::from_str::<std::collections::HashMap<String, LegendaryInstall>>(&data)
+        .ok()?
+        .remove(FORTNITE_APP_NAME)
+        .map(|install| install.install_path)
+}
+
+pub fn discover_install_path() -> Option<PathBuf> {
+    egl_install_path()
+        .or_else(|| Legendary::default_path().and_then(|path| legendary_install_path(&path)))
+        .or_else(|| {
+            Heroic::discover()
+                .ok()
+                .and_then(|heroic| legendary_install_path(&heroic.legendary_path()))
+        })
+}
+
+pub fn game_executable(configuration: &Configuration) -> Result<PathBuf, EpicError> {
+    let path = match configuration.game_path.is_empty() {
+        true => discover_install_path(),
+        false => Some(PathBuf::from(&configuration.game_path)),
+    };
+
+    path.map(|path| executable_from_path(&path))
+        .filter(|executable| executable.is_file())
+        .ok_or(EpicError::new(
+            EpicErrorKind::NotFound,
+            Some("Failed to find Fortnite, set its path in the settings"),
+        ))
+}
+
+pub fn launch_game(executable: &Path, arguments: &[String]) -> Result<Child, EpicError> {
+    std::process::Command::new(executable)
+        .args(arguments)
+        .current_dir(executable.parent().unwrap_or(Path::new(".")))
+        .spawn()
+        .map_err(|_| {
+            EpicError::new(
+                EpicErrorKind::IoError,
+                Some(format!("Failed to start {}", executable.display())),
+            )
+        })
+}
+
+//FortniteLauncher.exe starts the client and may exit, an instance is the process we started and its children
+pub struct GameInstance {
+    pub account_id: String,
+    pids: Vec<u32>,
+    child: Child,
+}
+
+impl GameInstance {
+    fn refresh(&mut self, processes: &[ProcessInfo]) {
+        //reaps the process we started so it does not stay a zombie
+        let _ = self.child.try_wait();
+
+        loop {
+            let children: Vec<u32> = processes
+                .iter()
+                .filter(|process| self.pids.contains(&process.parent_pid) && !self.pids.contains(&process.pid))
+                .map(|process| process.pid)
+                .collect();
+
+            if children.is_empty() {
+                break;
+            }
+
+            self.pids.extend(children);
+        }
+
+        self.pids.retain(|pid| process::is_alive(*pid));
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct GameInstances {
+    instances: Arc<Mutex<Vec<GameInstance>>>,
+}
+
+impl GameInstances {
+    pub fn track(&self, account_id: &str, child: Child) {
+        let instance = GameInstance {
+            account_id: account_id.to_string(),
+            pids: vec![child.id()],
+            child,
+        };
+
+        self.instances.lock().unwrap().push(instance);
+    }
+
+    //follows the processes started by each instance and forgets the finished ones
+    pub fn refresh(&self) {
+        let processes = process::list_processes().unwrap_or_default();
+        let mut instances = self.instances.lock().unwrap();
+
+        instances.iter_mut().for_each(|instance| instance.refresh(&processes));
+        instances.retain(|instance| !instance.pids.is_empty());
+    }
+
+    //the account ids of the running instances
+    pub fn in_game(&self) -> Vec<String> {
+        self.instances
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|instance| instance.account_id.clone())
+            .collect()
+    }
+
+    pub fn is_in_game(&self, account_id: &str) -> bool {
+        self.in_game().iter().any(|x| x == account_id)
+    }
+
+    //asks every process of the instance to close, the returned ones have to be waited for
+    pub fn stop(&self, account_id: &str) -> Result<Vec<u32>, EpicError> {
+        self.refresh();
+
+        let instances = self.instances.lock().unwrap();
+        let instance = instances
+            .iter()
+            .find(|instance| instance.account_id == account_id)
+            .ok_or(EpicError::new(
+                EpicErrorKind::NotFound,
+                Some(format!("Account {} is not in game", account_id)),
+            ))?;
+
+        for pid in instance.pids.iter() {
+            let _ = process::terminate(*pid, false);
+        }
+
+        Ok(instance.pids.clone())
+    }
+}
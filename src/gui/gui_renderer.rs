@@ -1,11 +1,13 @@
+use crate::accounts::{account_labels, current_launcher_account, AccountEvent, AccountLabel, AccountService, RemovalMode};
 use crate::config::Configuration;
-use crate::egl::epic_get_remember_me_data;
+use crate::health::AccountHealth;
 use crate::epic::DeviceAuthorization;
+use crate::secret_store::{self, SecretStoreKind};
 use egui_toast::{ Toast, ToastKind, ToastOptions, Toasts };
 
 use std::sync::Arc;
-use std::time::Instant;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc::UnboundedReceiver, Mutex};
 
 use egui::{
     include_image,
@@ -14,7 +16,6 @@ use egui::{
     CursorIcon,
     Label,
     Layout,
-    RichText,
     Sense,
     Pos2,
     Image,
@@ -56,27 +57,33 @@ impl AppDeviceAuthorization {
 }
 
 pub struct App {
-    pub configuration: Arc<Mutex<Configuration>>,
     pub toasts: Toasts,
-    pub(crate) accounts: Vec<String>,
+    pub(crate) accounts: Vec<AccountLabel>,
     pub(crate) current_account: Option<String>,
+    pub(crate) in_game: Vec<String>,
+    pub(crate) superseded_device_auths: usize,
     pub runtime_settings:Arc<std::sync::Mutex<RuntimeSettings>>,
     pub(crate) window_manager: WindowManager,
     pub event_manager: EventManager,
+    pub(crate) account_service: AccountService,
+    pub(crate) account_events: UnboundedReceiver<AccountEvent>,
 }
 
 impl Default for App {
     fn default() -> Self {
         let configuration = Configuration::new().expect("Failed to load configuration");
-        let accounts = configuration.accounts.clone();
+        let accounts = account_labels(&configuration);
+        let superseded_device_auths = configuration.superseded_device_auths.len();
+        let configuration = Arc::new(Mutex::new(configuration));
+        let (account_event_sender, account_events) = tokio::sync::mpsc::unbounded_channel();
 
         Self {
-            configuration: Arc::new(Mutex::new(configuration)),
-            accounts: accounts
-                .iter()
-                .map(|x| x.display_name.clone())
-                .collect(),
+            account_service: AccountService::new(configuration).with_events(account_event_sender),
+            account_events,
+            accounts,
             current_account: None,
+            in_game: Vec::new(),
+            superseded_device_auths,
             toasts: Toasts::new()
                 .anchor(Align2::RIGHT_BOTTOM, (-5.0, -5.0))
                 .direction(egui::Direction::BottomUp),
@@ -95,13 +102,18 @@ impl App {
             app.set_window(EWindow::AddAccount);
         }
 
-        match epic_get_remember_me_data() {
+        app.account_service.watch_game_instances(Duration::from_secs(2));
+        app.account_service.watch_profiles(Duration::from_secs(60 * 60));
+
+        let current_account = current_launcher_account(&app.account_service.configuration().blocking_lock());
+
+        match current_account {
             Ok(account) => {
                 app.current_account = Some(account.display_name.clone());
             }
             Err(_) => {
                 app.toasts.add(Toast {
-                    text: "Failed to get your EpicGames current account".into(),
+                    text: "Failed to get your current launcher account".into(),
                     kind: ToastKind::Error,
                     options: ToastOptions::default()
                         .duration_in_seconds(5.0)
@@ -114,10 +126,32 @@ impl App {
     }
 }
 
+impl App {
+    //locks the master password vault when idle and asks for it while locked
+    fn handle_auto_lock(&mut self, ctx: &egui::Context) {
+        if secret_store::active_store() != SecretStoreKind::MasterPassword {
+            return;
+        }
+
+        if ctx.input(|i| !i.events.is_empty()) {
+            secret_store::touch();
+        }
+
+        secret_store::lock_if_idle();
+
+        if secret_store::is_locked() && !self.window_manager.is_showing(&EWindow::Unlock) {
+            self.set_window(EWindow::Unlock);
+        }
+
+        ctx.request_repaint_after(Duration::from_secs(1));
+    }
+}
+
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.toasts.show(ctx);
         self.handle_events();
+        self.handle_auto_lock(ctx);
 
         egui::CentralPanel::default().show(ctx, |ui| {
             //disable window controls if a subwindow is opened and should render
@@ -133,11 +167,11 @@ impl eframe::App for App {
             });
 
             let mut sorted_accounts = self.accounts.clone();
-            sorted_accounts.sort_by(|a, b| b.len().partial_cmp(&a.len()).unwrap());
+            sorted_accounts.sort_by(|a, b| b.display_name.len().partial_cmp(&a.display_name.len()).unwrap());
 
             let longest_account = sorted_accounts.first();
 
-            if let Some(username) = longest_account {
+            if let Some(longest_account) = longest_account {
                 const FONT_SIZE: f32 = 15.0;
 
                 let base_y_offset = ui.available_height() / 5.0;
@@ -148,7 +182,7 @@ impl eframe::App for App {
 
                 let text_size = ui
                     .painter()
-                    .layout_no_wrap(username.clone(), font_id.clone(), TEXT_COLOR)
+                    .layout_no_wrap(longest_account.display_name.clone(), font_id.clone(), TEXT_COLOR)
                     .size();
 
                 let text_middle_screen = (ui.available_width() - text_size.x) / 2.0;
@@ -194,20 +228,73 @@ impl eframe::App for App {
                         }));
                     }
 
-                    if
-                        ui
-                            .put(
-                                rect_text,
-                                Label::new(
-                                    rich_montserrat_text(account.clone(), FONT_SIZE).strong()
-                                ).sense(Sense::click())
-                            )
-                            .on_hover_cursor(CursorIcon::PointingHand)
-                            .clicked()
-                    {
-                        self.swap_account(account.clone());
+                    let in_game = self.in_game.contains(&account.account_id);
+                    let needs_attention = account.health.needs_attention();
+                    let mut text = rich_montserrat_text(account.display_name.clone(), FONT_SIZE).strong();
+                    if in_game {
+                        text = text.color(PRIMARY_COLOR);
+                    } else if needs_attention {
+                        text = text.color(DELETE_COLOR);
+                    }
+
+                    let mut response = ui
+                        .put(rect_text, Label::new(text).sense(Sense::click()))
+                        .on_hover_cursor(CursorIcon::PointingHand);
+
+                    if in_game {
+                        response = response.on_hover_text("In game");
+                    } else if needs_attention {
+                        response = response.on_hover_text(account.health.to_string());
+                    }
+
+                    if response.clicked() {
+                        self.swap_account(account.account_id.clone());
                     }
 
+                    //right click to start the game without going through the launcher
+                    response.context_menu(|ui| {
+                        if in_game {
+                            if ui.button("Stop game").clicked() {
+                                self.stop_game(account.account_id.clone());
+                                ui.close_menu();
+                            }
+                        } else if ui.button("Play as").clicked() {
+                            self.play_as(account.account_id.clone());
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Launch arguments").clicked() {
+                            self.set_window(EWindow::AccountOptions(account.account_id.clone()));
+                            ui.close_menu();
+                        }
+
+                        //adding the account again replaces its device auth
+                        if account.health.is_broken() && ui.button("Link again").clicked() {
+                            self.set_window(EWindow::AddAccount);
+                            ui.close_menu();
+                        }
+
+                        if account.health == AccountHealth::ActionRequired && ui.button("Complete required action").clicked() {
+                            self.complete_corrective_action(account.account_id.clone());
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Device auths").on_hover_text("Lists the devices allowed to log in as this account").clicked() {
+                            self.set_window(EWindow::DeviceAuths(account.account_id.clone()));
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Log out everywhere").on_hover_text("Ends every launcher, game and device session of this account").clicked() {
+                            self.log_out_everywhere(account.account_id.clone());
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Remove locally only").on_hover_text("Keeps the device auth valid on Epic, for when Epic can't be reached").clicked() {
+                            self.remove_account(account.account_id.clone(), RemovalMode::LocalOnly);
+                            ui.close_menu();
+                        }
+                    });
+
                     let mut rect_delete = rect_text.clone();
 
                     rect_delete.min.x = rect_text.max.x + ui.style().spacing.item_spacing.x;
@@ -228,7 +315,7 @@ impl eframe::App for App {
                             .on_hover_cursor(CursorIcon::PointingHand)
                             .clicked()
                     {
-                        self.remove_account(account.clone());
+                        self.remove_account(account.account_id.clone(), RemovalMode::RevokeOnEpic);
                     }
                 }
             }
@@ -240,8 +327,9 @@ impl eframe::App for App {
                 ui.style_mut().spacing.item_spacing.y = 5.0;
 
                 if
-                    self.current_account.is_some() &&
-                    !self.accounts.contains(&self.current_account.clone().unwrap())
+                    self.current_account
+                        .as_ref()
+                        .is_some_and(|current| !self.accounts.iter().any(|x| &x.display_name == current))
                 {
                     if
                         ui
@@ -251,28 +339,25 @@ impl eframe::App for App {
                             )
                             .clicked()
                     {
-                        self.link_egl_account();
+                        self.link_current_account();
                     }
                 }
 
+                //left behind by duplicated accounts, deleting them needs the accounts unlocked
+                if self.superseded_device_auths > 0
+                    && add_button(ui, format!("Delete {} replaced device auths", self.superseded_device_auths), EColor::Delete)
+                        .on_hover_text("They are no longer used by the manager but still allow logging in")
+                        .clicked()
+                {
+                    self.delete_superseded_device_auths();
+                }
+
                 if add_button(ui, "Kill EGL",  EColor::Primary).clicked() {
-                    let result = Self::kill_epic_games_launcher().map(|_| {
-                        Toast {
-                            kind: ToastKind::Success,
-                            text: egui::WidgetText::RichText(
-                                RichText::new("Killed EpicGamesLauncher successfully")
-                            ),
-                            options: ToastOptions::default()
-                                .duration_in_seconds(5.0)
-                                .show_icon(true)
-                                .show_progress(true),
-                        }
-                    });
+                    self.kill_epic_games_launcher();
+                }
 
-                    self.toasts.add(match result {
-                        Ok(toast) => toast,
-                        Err(epic_error) => epic_error.to_toast(),
-                    });
+                if add_button(ui, "Check accounts",  EColor::Primary).clicked() {
+                    self.check_health();
                 }
 
                     //afficher le bouton pour ajouter un compte
@@ -1,103 +1,130 @@
-use std::sync::Arc;
-
-use crate::{
-    epic::EpicError,
-    process::{get_process_pid, kill_process},
-};
+use crate::accounts::{AccountEvent, AccountService, RemovalMode};
 
 use super::{
     gui_renderer::App,
-    gui_workers_proc::{
-        link_egl_account_proc, remove_account_proc, swap_account_proc,
-    }, window::{EventKind, EWindow, WindowSharedData, WindowDescriptor},
+    gui_workers_proc::spawn_account_task,
+    window::{EventKind, EWindow, WindowSharedData, WindowDescriptor},
 };
 
 impl App {
     pub fn handle_events(&mut self) {
         if let Ok(event) = self.event_manager.1.try_recv() {
             match event {
-                EventKind::Accounts(accounts) => {
-                    self.accounts = accounts;
-                },
                 EventKind::AddToast(toast) => {
                     self.toasts.add(toast);
                 },
-                EventKind::CurrentAccount(account) => {
+            }
+        }
+
+        while let Ok(event) = self.account_events.try_recv() {
+            match event {
+                AccountEvent::AccountsChanged(accounts) => {
+                    self.accounts = accounts;
+                },
+                AccountEvent::CurrentAccountChanged(account) => {
                     self.current_account = account;
                 },
+                AccountEvent::GameInstancesChanged(in_game) => {
+                    self.in_game = in_game;
+                },
+                AccountEvent::SupersededDeviceAuthsChanged(count) => {
+                    self.superseded_device_auths = count;
+                },
+                //the add account window shows its own device code
+                AccountEvent::Progress(_) | AccountEvent::DeviceCode(_) => {},
             }
         }
     }
 
     pub fn set_window(&mut self, kind:EWindow) {
         self.window_manager.set_window(WindowDescriptor { kind: kind, runtime_settings: self.runtime_settings.clone() }, WindowSharedData {
-            configuration: Arc::clone(&self.configuration),
+            configuration: self.account_service.configuration(),
+            account_service: self.account_service.clone(),
             event_sender: self.event_manager.0.clone(),
             accounts: self.accounts.clone()
         });
     }
 
-    pub fn link_egl_account(&self) {
-        let event_sender = self.event_manager.0.clone();
-        let configuration_mtx = Arc::clone(&self.configuration);
-        tokio::spawn(async move {
-            let toast = link_egl_account_proc(configuration_mtx, event_sender.clone())
-                .await
-                .unwrap_or_else(|error| error.to_toast());
+    pub fn link_current_account(&self) {
+        let account_service = self.account_service.clone();
 
-            let _ = event_sender.send(super::window::EventKind::AddToast(toast)).await;
+        spawn_account_task(self.event_manager.0.clone(), async move {
+            account_service.link_current_account().await
         });
     }
 
-    pub fn swap_account(&self, display_name: impl Into<String>) {
-        let configuration_mtx = Arc::clone(&self.configuration);
-        let event_sender = self.event_manager.0.clone();
+    pub fn swap_account(&self, account_id: impl Into<String>) {
+        let account_service = self.account_service.clone();
+        let account_id = account_id.into();
 
-        let display_name = display_name.into();
-        tokio::spawn(async move {
-            let result = swap_account_proc(configuration_mtx, display_name.clone()).await;
+        spawn_account_task(self.event_manager.0.clone(), async move {
+            account_service.swap_account(&account_id).await
+        });
+    }
 
-            if result.is_ok() {
-                let _ = event_sender.send(super::window::EventKind::CurrentAccount(Some(display_name))).await;
-            }
+    pub fn remove_account(&self, account_id: impl Into<String>, mode: RemovalMode) {
+        let account_service = self.account_service.clone();
+        let account_id = account_id.into();
 
-            let toast = result.unwrap_or_else(|error| error.to_toast());
+        spawn_account_task(self.event_manager.0.clone(), async move {
+            account_service.remove_account(&account_id, mode).await
+        });
+    }
 
-            let _ = event_sender.send(super::window::EventKind::AddToast(toast)).await;
+    pub fn play_as(&self, account_id: impl Into<String>) {
+        let account_service = self.account_service.clone();
+        let account_id = account_id.into();
+
+        spawn_account_task(self.event_manager.0.clone(), async move {
+            account_service.play_as(&account_id).await
         });
     }
 
-    pub fn remove_account(&self, display_name: impl Into<String>) {
-        let configuration_mtx = Arc::clone(&self.configuration);
-        let event_sender = self.event_manager.0.clone();
+    pub fn stop_game(&self, account_id: impl Into<String>) {
+        let account_service = self.account_service.clone();
+        let account_id = account_id.into();
+
+        spawn_account_task(self.event_manager.0.clone(), async move {
+            account_service.stop_game(&account_id).await
+        });
+    }
 
-        let display_name = display_name.into();
-        tokio::spawn(async move {
-            let toast = remove_account_proc(configuration_mtx, event_sender.clone(), display_name)
-                .await
-                .unwrap_or_else(|error| error.to_toast());
+    pub fn complete_corrective_action(&self, account_id: impl Into<String>) {
+        let account_service = self.account_service.clone();
+        let account_id = account_id.into();
 
-            let _ = event_sender.send(super::window::EventKind::AddToast(toast)).await;
+        spawn_account_task(self.event_manager.0.clone(), async move {
+            account_service.complete_corrective_action(&account_id).await
         });
     }
 
-    pub fn kill_epic_games_launcher() -> Result<(), EpicError> {
-        unsafe {
-            let pid = get_process_pid("EpicGamesLauncher.exe".to_string()).map_err(|_| {
-                EpicError::new(
-                    crate::epic::EpicErrorKind::NotFound,
-                    Some("Failed to find EpicGamesLauncher.exe"),
-                )
-            })?;
-
-            kill_process(pid).map_err(|_| {
-                EpicError::new(
-                    crate::epic::EpicErrorKind::Other,
-                    Some("Failed to kill EpicGamesLauncher.exe"),
-                )
-            })?;
-        }
+    pub fn log_out_everywhere(&self, account_id: impl Into<String>) {
+        let account_service = self.account_service.clone();
+        let account_id = account_id.into();
+
+        spawn_account_task(self.event_manager.0.clone(), async move {
+            account_service.log_out_everywhere(&account_id).await
+        });
+    }
+
+    pub fn check_health(&self) {
+        let account_service = self.account_service.clone();
+
+        spawn_account_task(self.event_manager.0.clone(), async move {
+            account_service.check_health().await
+        });
+    }
+
+    pub fn delete_superseded_device_auths(&self) {
+        let account_service = self.account_service.clone();
+
+        spawn_account_task(self.event_manager.0.clone(), async move {
+            account_service.delete_superseded_device_auths().await
+        });
+    }
 
-        Ok(())
+    //waits for the launcher to exit, it must not block the render loop
+    pub fn kill_epic_games_launcher(&self) {
+        spawn_account_task(self.event_manager.0.clone(), AccountService::kill_epic_games_launcher());
     }
 }
@@ -3,10 +3,14 @@ pub mod gui_helper;
 pub mod gui_renderer;
 pub mod gui_workers;
 pub(crate) mod gui_workers_proc;
+pub mod toasts;
 pub mod window;
 
 mod windows {
+    pub mod account_options;
     pub mod add_account;
     pub mod clone_configuration;
+    pub mod device_auths;
     pub mod settings;
+    pub mod unlock;
 }
\ No newline at end of file
@@ -0,0 +1,47 @@
+use egui::RichText;
+use egui_toast::{Toast, ToastKind, ToastOptions};
+
+use crate::{accounts::AccountOutcome, epic::EpicError};
+
+//turns the outcome of the core workflows into something the user can see
+pub trait ToToast {
+    fn to_toast(&self) -> Toast;
+}
+
+impl ToToast for EpicError {
+    fn to_toast(&self) -> Toast {
+        Toast {
+            kind: ToastKind::Error,
+            text: egui::WidgetText::RichText(self.to_string().into()),
+            options: ToastOptions::default().duration_in_seconds(10.),
+        }
+    }
+}
+
+impl ToToast for AccountOutcome {
+    fn to_toast(&self) -> Toast {
+        let (kind, duration) = match self {
+            AccountOutcome::Added(_) | AccountOutcome::Removed(_) | AccountOutcome::RemovedLocally(_) => (ToastKind::Success, 10.0),
+            AccountOutcome::LauncherKilled => (ToastKind::Success, 5.0),
+            _ => (ToastKind::Info, 10.0),
+        };
+
+        Toast {
+            kind,
+            text: RichText::new(self.to_string()).into(),
+            options: ToastOptions::default()
+                .duration_in_seconds(duration)
+                .show_progress(true)
+                .show_icon(true),
+        }
+    }
+}
+
+impl<T: ToToast, E: ToToast> ToToast for Result<T, E> {
+    fn to_toast(&self) -> Toast {
+        match self {
+            Ok(value) => value.to_toast(),
+            Err(error) => error.to_toast(),
+        }
+    }
+}
@@ -1,12 +1,17 @@
 use std::sync::Arc;
 
 use egui_toast::Toast;
+
+use crate::accounts::{AccountLabel, AccountService};
 use tokio::sync::{ mpsc::Sender, Mutex, mpsc::Receiver };
 
 use super::windows::{
+    account_options::AccountOptionsWindow,
     add_account::AddAccountWindow,
     clone_configuration::{ CloneControlsData, CloneControlsWindow },
+    device_auths::DeviceAuthsWindow,
     settings::RuntimeSettings,
+    unlock::UnlockWindow,
 };
 
 #[derive(PartialEq, Clone, Hash, Eq, Debug)]
@@ -14,6 +19,9 @@ pub enum EWindow {
     AddAccount,
     CloneSettings(CloneControlsData),
     Settings,
+    Unlock,
+    AccountOptions(String),
+    DeviceAuths(String),
 }
 
 #[derive(Clone)]
@@ -62,8 +70,32 @@ impl WindowManager {
                     ),
                 ));
             }
+            EWindow::Unlock => {
+                self.current_window = Some((
+                    window.kind.clone(),
+                    Box::new(UnlockWindow::new(shared_data, window.clone())),
+                ));
+            }
+            EWindow::AccountOptions(_account_id) => {
+                self.current_window = Some((
+                    window.kind.clone(),
+                    Box::new(AccountOptionsWindow::new(shared_data, window.clone())),
+                ));
+            }
+            EWindow::DeviceAuths(_account_id) => {
+                self.current_window = Some((
+                    window.kind.clone(),
+                    Box::new(DeviceAuthsWindow::new(shared_data, window.clone())),
+                ));
+            }
         }
     }
+
+    pub fn is_showing(&self, kind: &EWindow) -> bool {
+        self.current_window
+            .as_ref()
+            .is_some_and(|(current_kind, window)| current_kind == kind && window.should_appear())
+    }
 }
 
 pub trait SubWindow {
@@ -76,9 +108,7 @@ pub trait SubWindow {
 }
 
 pub enum EventKind {
-    Accounts(Vec<String>),
     AddToast(Toast),
-    CurrentAccount(Option<String>),
 }
 
 pub type EventSender = Sender<EventKind>;
@@ -87,6 +117,7 @@ pub type EventManager = (EventSender, EventReceiver);
 
 pub struct WindowSharedData {
     pub configuration: Arc<Mutex<crate::config::Configuration>>,
-    pub accounts: Vec<String>,
+    pub account_service: AccountService,
+    pub accounts: Vec<AccountLabel>,
     pub event_sender: EventSender,
 }
@@ -0,0 +1,95 @@
+use egui::{Align2, Label, Widget};
+
+use crate::gui::{
+    gui_helper::{add_button, centerer, rich_montserrat_text, EColor},
+    gui_workers_proc::spawn_account_task,
+    window::{EWindow, SubWindow, WindowDescriptor, WindowSharedData},
+};
+
+pub struct AccountOptionsWindow {
+    account_id: String,
+    display_name: String,
+    launch_args: String,
+    shared_data: WindowSharedData,
+    should_close: bool,
+}
+
+impl SubWindow for AccountOptionsWindow {
+    fn new(shared_data: WindowSharedData, window_descriptor: WindowDescriptor) -> Self
+    where
+        Self: Sized,
+    {
+        let account_id = match window_descriptor.kind {
+            EWindow::AccountOptions(account_id) => account_id,
+            _ => String::new(),
+        };
+
+        let (display_name, launch_args) = shared_data
+            .configuration
+            .blocking_lock()
+            .accounts
+            .iter()
+            .find(|x| x.account_id() == Some(account_id.as_str()))
+            .map(|x| (x.display_name.clone(), x.launch_args.clone()))
+            .unwrap_or_default();
+
+        Self {
+            account_id,
+            display_name,
+            launch_args,
+            shared_data,
+            should_close: false,
+        }
+    }
+
+    fn create_window<'a>(&self, _ui: &egui::Ui) -> egui::Window<'a>
+    where
+        Self: Sized,
+    {
+        egui::Window::new("Account options")
+            .resizable(false)
+            .collapsible(false)
+            .movable(false)
+            .title_bar(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+    }
+
+    fn render(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        self.create_window(ui).show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add(Label::new(rich_montserrat_text(self.display_name.clone(), 18.)));
+            });
+
+            egui::TextEdit::singleline(&mut self.launch_args)
+                .hint_text("Launch arguments")
+                .ui(ui)
+                .on_hover_text("Appended to the Fortnite command line by Play as");
+
+            centerer(ui, "_account_options", |ui| {
+                if add_button(ui, "Save", EColor::Primary).clicked() {
+                    let account_service = self.shared_data.account_service.clone();
+                    let account_id = self.account_id.clone();
+                    let launch_args = self.launch_args.clone();
+
+                    spawn_account_task(self.shared_data.event_sender.clone(), async move {
+                        account_service.set_launch_args(&account_id, &launch_args).await
+                    });
+
+                    self.close();
+                }
+
+                if add_button(ui, "Close", EColor::Delete).clicked() {
+                    self.close();
+                }
+            });
+        });
+    }
+
+    fn close(&mut self) {
+        self.should_close = true;
+    }
+
+    fn should_appear(&self) -> bool {
+        !self.should_close
+    }
+}
@@ -1,16 +1,27 @@
 use std::sync::Arc;
-use tokio::sync::{mpsc::{Sender, Receiver}, Mutex};
+use tokio::{sync::{mpsc::{Sender, Receiver}, Mutex}, task::JoinHandle};
 
 use egui::{FontId, Align2, CursorIcon, Sense, Label, RichText, OpenUrl, ComboBox};
-use egui_toast::{Toast, ToastOptions};
 
-use crate::{gui::{window::{SubWindow, WindowSharedData, WindowDescriptor, EventKind, EventSender}, gui_constants::TEXT_COLOR, gui_renderer::AppDeviceAuthorization, gui_helper::{rich_montserrat_text, centerer, add_button, EColor}}, epic::{TokenType, DeviceAuthorization, self, EpicError, token_types, DeviceAuth, Token}, epic_clients::{self, AuthClient}, get_client};
+use crate::{accounts::{self, AccountService}, gui::{toasts::ToToast, window::{SubWindow, WindowSharedData, WindowDescriptor, EventKind, EventSender}, gui_constants::TEXT_COLOR, gui_renderer::AppDeviceAuthorization, gui_helper::{rich_montserrat_text, centerer, add_button, EColor}}, epic::{TokenType, DeviceAuthorization, self, token_types, DeviceAuth, Token}, epic_clients::{self, AuthClient}, get_client, loopback::{self, LoopbackListener}};
 
 #[derive(Debug, Default, Clone)]
 pub struct CredentialsBuffer {
     pub data:String,
     pub client_id:String,
-    pub client_secret:String
+    pub client_secret:String,
+    pub client:Option<AuthClient<'static>>
+}
+
+impl CredentialsBuffer {
+    //a custom client id and secret win over the selected client
+    pub fn auth_client(&self) -> Option<AuthClient<'_>> {
+        if !self.client_id.trim().is_empty() && !self.client_secret.trim().is_empty() {
+            return Some(AuthClient { id: self.client_id.trim(), name: "Custom client", secret: self.client_secret.trim() });
+        }
+
+        self.client
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -35,7 +46,11 @@ pub struct AddAccountWindow {
     thread_state:Arc<Mutex<bool>>, //thread unique par window
     should_close: bool,
     close_window_communication: (Sender<bool>, Receiver<bool>),
-    advanced_mode: bool
+    advanced_mode: bool,
+    //the local login page waiting for an authorization code
+    browser_login: Option<JoinHandle<()>>,
+    //only for clients allowing a redirect to 127.0.0.1
+    redirect_to_app: bool
 }
 
 const USE_THIS_CODE_TEXT:&'static str = "Use this code to link your account to this application through epicgames.com/activate";
@@ -53,25 +68,30 @@ macro_rules! manage_error {
     };
 }
 
-pub async fn add_account_proc<'a>(token:Token<'a>, client:AuthClient<'a>, event_sender:EventSender, configuration_mtx:Arc<Mutex<crate::config::Configuration>>) {
-    let account_result = epic::token(token, client).await;
-    if account_result.is_err() {
-        let _ = event_sender.send(EventKind::AddToast(account_result.clone().unwrap_err().to_toast())).await;
-    }
+fn client_selector(ui:&mut egui::Ui, id:&str, selected:&mut Option<AuthClient<'static>>) {
+    let mut clients = epic_clients::AuthClient::clients();
+    clients.sort_by_key(|x| std::cmp::Reverse(x.name.len()));
 
-    let account = account_result.as_ref().unwrap();
+    let client_selector_size = ui.painter().layout_no_wrap(clients.first().unwrap().name.to_string(), FontId::monospace(14.), ui.style().visuals.text_color()).size();
 
-    let mut configuration = configuration_mtx.lock().await;
-    let add_account_result = configuration.add_account(crate::config::AddAccountProvider::EpicAccount(account)).await;
-    let _ = configuration.flush();
+    centerer( ui, id, |ui| {
+        ComboBox::from_label("Client")
+        .selected_text(selected.map(|x| x.name).unwrap_or("Select a client"))
+        .width(client_selector_size.x)
+        .show_ui(ui, |ui| {
+            clients.iter().for_each(|client| {
+                if ui.selectable_label(*selected == Some(*client), client.name).clicked() {
+                    *selected = Some(*client);
+                }
+            });
+        });
+    });
+}
 
-    if add_account_result.is_err() {
-        let error = add_account_result.unwrap_err();
-        let epic_error = EpicError::new(epic::EpicErrorKind::Other, Some(format!("Add Account failed with error : {}", error.to_string())));
-        let _ = event_sender.send(EventKind::AddToast(epic_error.to_toast())).await;
-    }
+pub async fn add_account_proc<'a>(token:Token<'a>, client:AuthClient<'a>, event_sender:EventSender, account_service:AccountService) {
+    let result = account_service.add_account(token, client).await;
 
-    let _ = event_sender.send(EventKind::Accounts(configuration.accounts.iter().map(|x| x.display_name.clone()).collect())).await;
+    let _ = event_sender.send(EventKind::AddToast(result.to_toast())).await;
 }
 
 impl AddAccountWindow {
@@ -82,7 +102,7 @@ impl AddAccountWindow {
 
         let thread_state = self.thread_state.clone();
         let event_sender = self.shared_data.event_sender.clone();
-        let configuration_mtx = Arc::clone(&self.shared_data.configuration);
+        let account_service = self.shared_data.account_service.clone();
         tokio::spawn(async move {
             loop {
                 if *thread_state.lock().await == false {
@@ -93,14 +113,7 @@ impl AddAccountWindow {
 
                 //if code is none or expired, get a new one
                 if current_device_code.is_none() || current_device_code.as_ref().unwrap().is_expired() {
-                    let client_token_result = epic::token(
-                        epic::Token::ClientCredentials,
-                        AuthClient::get("fortniteNewSwitchGameClient").unwrap()
-                    ).await;
-
-
-                    let client_token = manage_error!(client_token_result, event_sender);
-                    let device_authorization_result = client_token.get_device_authorization().await;
+                    let device_authorization_result = accounts::request_device_code().await;
                     let device_authorization = AppDeviceAuthorization::from( manage_error!(device_authorization_result, event_sender));
                     
                     let _ = device_code_communication.send(device_authorization.clone()).await;
@@ -120,24 +133,11 @@ impl AddAccountWindow {
 
                 //add account into configuration and show a toast to the user
 
-                let mut configuration = configuration_mtx.lock().await;
-                let add_account_result = configuration.add_account(crate::config::AddAccountProvider::EpicAccount(&account)).await.map_err(|_| EpicError::new(epic::EpicErrorKind::Other, Some("Failed to add account to configuration")));
+                let add_account_result = account_service.add_epic_account(&account).await;
+                let _ = account.kill_session().await;
+                let outcome = manage_error!(add_account_result, event_sender);
 
-                let _ = manage_error!(add_account_result, event_sender);
-
-                let _ = event_sender.send(crate::gui::window::EventKind::AddToast(
-                    Toast {
-                        kind: egui_toast::ToastKind::Success,
-                        text: RichText::new(format!("Linked {} succesfully.", account.display_name.as_ref().unwrap())).into(),
-                        options: ToastOptions::default()
-                            .duration_in_seconds(10.0)
-                            .show_progress(true)
-                            .show_icon(true),
-                    }
-                )).await;
-
-                let _ = event_sender.send(crate::gui::window::EventKind::Accounts(configuration.accounts.iter().map(|x| x.display_name.clone()).collect())).await;
-                let _ = configuration.flush();
+                let _ = event_sender.send(crate::gui::window::EventKind::AddToast(outcome.to_toast())).await;
 
                 let _ = close_window_communication.send(true).await;
 
@@ -149,6 +149,135 @@ impl AddAccountWindow {
     }
 }
 
+impl AddAccountWindow {
+    fn credentials_buffer(&mut self) -> &mut CredentialsBuffer {
+        match self.add_type {
+            TokenType::RefreshToken => &mut self.refresh_token_buffer,
+            TokenType::AuthorizationCode => &mut self.authorization_code_buffer,
+            _ => &mut self.exchange_code_buffer,
+        }
+    }
+
+    //the authorization code is caught by a local page instead of being pasted in the form
+    fn start_browser_login(&mut self, ctx:&egui::Context, buffer:CredentialsBuffer) {
+        let event_sender = self.shared_data.event_sender.clone();
+
+        let Some(client) = buffer.auth_client() else {
+            return;
+        };
+
+        let listener = match LoopbackListener::bind(&client) {
+            Ok(listener) => listener,
+            Err(error) => {
+                let _ = event_sender.try_send(EventKind::AddToast(error.to_toast()));
+                return;
+            }
+        };
+
+        let url = match self.redirect_to_app {
+            true => loopback::authorize_url(&client, &listener.local_url(), listener.state()),
+            false => listener.local_url(),
+        };
+        ctx.open_url(OpenUrl { url, new_tab: true });
+
+        //a new login replaces the one still waiting
+        if let Some(browser_login) = self.browser_login.take() {
+            browser_login.abort();
+        }
+
+        let account_service = self.shared_data.account_service.clone();
+        self.browser_login = Some(tokio::spawn(async move {
+            let Some(client) = buffer.auth_client() else {
+                return;
+            };
+
+            let result = account_service.add_with_browser(&listener, client).await;
+            let _ = event_sender.send(EventKind::AddToast(result.to_toast())).await;
+        }));
+    }
+
+    //the refresh token, authorization code and exchange code forms only differ by their code
+    fn credentials_form(&mut self, ui:&mut egui::Ui) {
+        let add_type = self.add_type;
+        let code_label = match add_type {
+            TokenType::RefreshToken => "Refresh Token",
+            TokenType::AuthorizationCode => "Authorization Code",
+            _ => "Exchange Code",
+        };
+
+        ui.vertical_centered(|ui| {
+            ui.add(Label::new(rich_montserrat_text(format!("Add account with {}", code_label), 18.)))
+        });
+
+        let buffer = self.credentials_buffer();
+
+        centerer(ui, "_code", |ui| {
+            ui.text_edit_singleline(&mut buffer.data);
+            ui.label(code_label);
+        });
+
+        client_selector(ui, "_client_selector", &mut buffer.client);
+
+        ui.collapsing("Custom client", |ui| {
+            centerer(ui, "_custom_client_id", |ui| {
+                ui.text_edit_singleline(&mut buffer.client_id);
+                ui.label("Client Id");
+            });
+
+            centerer(ui, "_custom_client_secret", |ui| {
+                ui.text_edit_singleline(&mut buffer.client_secret);
+                ui.label("Client Secret");
+            });
+        });
+
+        let clickable = !buffer.data.trim().is_empty() && buffer.auth_client().is_some();
+        let can_use_browser = buffer.auth_client().is_some();
+        let buffer = buffer.clone();
+
+        if add_type == TokenType::AuthorizationCode {
+            ui.checkbox(&mut self.redirect_to_app, "Epic redirects back to this app")
+                .on_hover_text("The client must allow http://127.0.0.1 redirects, the game clients don't");
+
+            centerer(ui, "_browser_login", |ui| {
+                ui.scope(|ui| {
+                    ui.set_enabled(can_use_browser);
+                    if add_button(ui, "Log in with my browser", EColor::Primary).clicked() {
+                        self.start_browser_login(ui.ctx(), buffer.clone());
+                    }
+                });
+            });
+        }
+
+        centerer(ui, "_actions", |ui| {
+            ui.scope(|ui| {
+                ui.set_enabled(clickable);
+                if add_button(ui, "Add Account", EColor::Primary).clicked() {
+                    let event_sender = self.shared_data.event_sender.clone();
+                    let account_service = self.shared_data.account_service.clone();
+                    tokio::spawn(async move {
+                        let Some(client) = buffer.auth_client() else {
+                            return;
+                        };
+
+                        let code = buffer.data.trim();
+                        let token = match add_type {
+                            TokenType::RefreshToken => Token::RefreshToken(code),
+                            TokenType::AuthorizationCode => Token::AuthorizationCode(code),
+                            _ => Token::ExchangeCode(code),
+                        };
+
+                        add_account_proc(token, client, event_sender, account_service).await;
+                    });
+                }
+            });
+
+            if add_button(ui, "Close",  EColor::Delete).clicked() {
+                self.close();
+            }
+        });
+    }
+}
+
 impl SubWindow for AddAccountWindow {
     fn new(shared_data:WindowSharedData, descriptor:WindowDescriptor) -> Self where Self: Sized {
         //WANTS_TO_SHOW_ACCOUNT_WINDOW.store(true, Ordering::Relaxed);
@@ -168,7 +297,9 @@ impl SubWindow for AddAccountWindow {
             thread_state: Arc::new(Mutex::new(true)),
             should_close: false,
             close_window_communication: tokio::sync::mpsc::channel(1),
-            advanced_mode:descriptor.runtime_settings.lock().unwrap().advanced_mode
+            advanced_mode:descriptor.runtime_settings.lock().unwrap().advanced_mode,
+            browser_login: None,
+            redirect_to_app: false
         };
 
         
@@ -190,9 +321,9 @@ impl SubWindow for AddAccountWindow {
         self.create_window(ui).show(ctx, |ui| {
 
             match self.add_type {
-                TokenType::RefreshToken => todo!(),
-                TokenType::AuthorizationCode => todo!(),
-                TokenType::ExchangeCode => todo!(),
+                TokenType::RefreshToken | TokenType::AuthorizationCode | TokenType::ExchangeCode => {
+                    self.credentials_form(ui);
+                },
                 TokenType::DeviceAuth => {
                     ui.vertical_centered(|ui| {
                         ui.add(Label::new(rich_montserrat_text("Add account with Device Auth", 18.)))
@@ -213,25 +344,7 @@ impl SubWindow for AddAccountWindow {
                         ui.label("Secret");
                     });
 
-                    let mut clients = epic_clients::AuthClient::clients();
-                    clients.sort_by(|a, b| b.name.len().cmp(&a.name.len()));
-
-                    let client_selector_size = ui.painter().layout_no_wrap(clients.first().unwrap().name.to_string(), FontId::monospace(14.), ui.style().visuals.text_color()).size();
-                    
-                    centerer( ui, "_client_selector", |ui| {
-                        ComboBox::from_label("Client")
-                        .selected_text(self.device_auth_buffer.client.map(|x| x.name).unwrap_or("Select a client"))
-                        .width(client_selector_size.x)
-                        .show_ui(ui, |ui| {
-                            clients.iter().for_each(|client| {
-                                if ui.selectable_label(self.device_auth_buffer.client == Some(*client), client.name).clicked() {
-                                    self.device_auth_buffer.client = Some(*client);
-                                }
-                            });
-                        });
-                    });
-
-
+                    client_selector(ui, "_client_selector", &mut self.device_auth_buffer.client);
 
                     let clickable = !self.device_auth_buffer.account_id.is_empty()
                     && !self.device_auth_buffer.device_id.is_empty()
@@ -245,7 +358,7 @@ impl SubWindow for AddAccountWindow {
                             if add_button(ui, "Add Account", EColor::Primary).clicked() {
                                 let buffer = self.device_auth_buffer.clone();
                                 let event_sender = self.shared_data.event_sender.clone();
-                                let configuration_mtx = self.shared_data.configuration.clone();
+                                let account_service = self.shared_data.account_service.clone();
                                 tokio::spawn(async move {
                                     let device_auth = DeviceAuth {
                                         account_id: buffer.account_id.clone(),
@@ -253,7 +366,7 @@ impl SubWindow for AddAccountWindow {
                                         secret: buffer.secret.clone(),
                                     };
     
-                                    let _ = add_account_proc(Token::DeviceAuth(&device_auth), buffer.client.unwrap(), event_sender, configuration_mtx).await;
+                                    let _ = add_account_proc(Token::DeviceAuth(&device_auth), buffer.client.unwrap(), event_sender, account_service).await;
                                 });
                             }
                         });
@@ -303,7 +416,7 @@ impl SubWindow for AddAccountWindow {
                         });
                     }
                 },
-                _ => todo!(),
+                TokenType::None => {},
             }
 
             if self.advanced_mode {
@@ -334,6 +447,10 @@ impl SubWindow for AddAccountWindow {
     }
 
     fn close(&mut self) {
+        if let Some(browser_login) = self.browser_login.take() {
+            browser_login.abort();
+        }
+
         let state = self.thread_state.clone();
         tokio::spawn(async move {
             *state.lock().await = false;
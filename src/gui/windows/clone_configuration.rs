@@ -2,11 +2,12 @@ use egui::{Align2, FontId, RichText, Label};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CloneControlsData {
-    pub clone_from: Option<String>,
-    pub clone_to: String,
+    pub clone_from: Option<AccountLabel>,
+    pub clone_to: AccountLabel,
 }
 
-use crate::gui::{window::{WindowSharedData, SubWindow, EWindow, WindowDescriptor}, gui_helper::{centerer, add_button, EColor}, gui_constants::TEXT_COLOR, gui_workers_proc::clone_settings_proc};
+use crate::accounts::AccountLabel;
+use crate::gui::{window::{WindowSharedData, SubWindow, EWindow, WindowDescriptor}, gui_helper::{centerer, add_button, EColor}, gui_constants::TEXT_COLOR, gui_workers_proc::spawn_account_task};
 
 pub struct CloneControlsWindow {
     information:CloneControlsData,
@@ -55,13 +56,14 @@ impl SubWindow for CloneControlsWindow {
                     .selected_text(
                         self.information.clone_from
                             .clone()
+                            .map(|x| x.display_name)
                             .unwrap_or(String::from("Select account"))
                     )
                     .show_ui(ui, |ui| {
                         let info = self.information.clone();
                         let mut selectable_string = |
                             ui: &mut egui::Ui,
-                            element: String
+                            element: AccountLabel
                         | {
                             let from = info.clone_from.clone();
                             let currently_selected =
@@ -69,7 +71,7 @@ impl SubWindow for CloneControlsWindow {
                                from.unwrap() == element.clone();
                             let mut response = ui.selectable_label(
                                 currently_selected,
-                                RichText::new(element.clone())
+                                RichText::new(element.display_name.clone())
                             );
                             if response.clicked() && !currently_selected {
                                 //select account
@@ -91,7 +93,7 @@ impl SubWindow for CloneControlsWindow {
 
                 ui.add(
                     Label::new(
-                        RichText::new(self.information.clone_to.clone())
+                        RichText::new(self.information.clone_to.display_name.clone())
                             .color(TEXT_COLOR)
                             .font(font.clone())
                             .strong()
@@ -106,20 +108,14 @@ impl SubWindow for CloneControlsWindow {
                     if add_button(ui, "Copy",  EColor::Primary).clicked() {
                         let info = self.information.clone();
 
-                        let configuration_mtx = self.shared_data.configuration.clone();
-                        let event_sender = self.shared_data.event_sender.clone();
+                        let account_service = self.shared_data.account_service.clone();
 
-                        let clone_from_username = info.clone_from.unwrap();
-                        let clone_to_username = info.clone_to;
+                        let clone_from_id = info.clone_from.unwrap().account_id;
+                        let clone_to_id = info.clone_to.account_id;
 
-                         tokio::spawn(async move {
-                            	let toast = 
-                                    clone_settings_proc(configuration_mtx, clone_from_username, clone_to_username)
-                                        .await
-                                        .unwrap_or_else(|error| error.to_toast());
-
-                                let _ = event_sender.send(crate::gui::window::EventKind::AddToast(toast)).await;
-                         });
+                        spawn_account_task(self.shared_data.event_sender.clone(), async move {
+                            account_service.clone_settings(&clone_from_id, &clone_to_id).await
+                        });
                     }
                 });
 
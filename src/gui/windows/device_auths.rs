@@ -0,0 +1,200 @@
+use egui::{Align2, Label, ScrollArea};
+use tokio::sync::mpsc::{Receiver, Sender};
+
+use crate::{
+    accounts::AccountService,
+    epic::{DeviceAuthInfo, DeviceAuthLocation},
+    gui::{
+        gui_helper::{add_button, centerer, rich_montserrat_text, EColor},
+        toasts::ToToast,
+        window::{EWindow, EventKind, SubWindow, WindowDescriptor, WindowSharedData},
+    },
+};
+
+type DeviceAuthsResult = Result<Vec<DeviceAuthInfo>, String>;
+
+pub struct DeviceAuthsWindow {
+    account_id: String,
+    display_name: String,
+    //the device auth the manager logs in with, it can only go away with the account
+    current_device_id: Option<String>,
+    device_auths: Option<DeviceAuthsResult>,
+    busy: bool,
+    device_auths_communication: (Sender<DeviceAuthsResult>, Receiver<DeviceAuthsResult>),
+    shared_data: WindowSharedData,
+    should_close: bool,
+}
+
+async fn list_device_auths(account_service: AccountService, account_id: String, sender: Sender<DeviceAuthsResult>) {
+    let device_auths = account_service
+        .device_auths(&account_id)
+        .await
+        .map_err(|error| error.to_string());
+
+    let _ = sender.send(device_auths).await;
+}
+
+fn describe_location(location: &Option<DeviceAuthLocation>) -> String {
+    let Some(location) = location else {
+        return "Unknown".to_string();
+    };
+
+    let place = location
+        .location
+        .clone()
+        .or(location.ip_address.clone())
+        .unwrap_or("Unknown place".to_string());
+
+    match &location.date_time {
+        Some(date_time) => format!("{} ({})", date_time, place),
+        None => place,
+    }
+}
+
+impl DeviceAuthsWindow {
+    fn reload(&mut self) {
+        self.busy = true;
+
+        tokio::spawn(list_device_auths(
+            self.shared_data.account_service.clone(),
+            self.account_id.clone(),
+            self.device_auths_communication.0.clone(),
+        ));
+    }
+
+    fn delete(&mut self, device_id: String) {
+        self.busy = true;
+
+        let account_service = self.shared_data.account_service.clone();
+        let account_id = self.account_id.clone();
+        let event_sender = self.shared_data.event_sender.clone();
+        let sender = self.device_auths_communication.0.clone();
+
+        tokio::spawn(async move {
+            let toast = account_service.delete_device_auth(&account_id, &device_id).await.to_toast();
+            let _ = event_sender.send(EventKind::AddToast(toast)).await;
+
+            list_device_auths(account_service, account_id, sender).await;
+        });
+    }
+}
+
+impl SubWindow for DeviceAuthsWindow {
+    fn new(shared_data: WindowSharedData, window_descriptor: WindowDescriptor) -> Self
+    where
+        Self: Sized,
+    {
+        let account_id = match window_descriptor.kind {
+            EWindow::DeviceAuths(account_id) => account_id,
+            _ => String::new(),
+        };
+
+        let (display_name, current_device_id) = shared_data
+            .configuration
+            .blocking_lock()
+            .accounts
+            .iter()
+            .find(|x| x.account_id() == Some(account_id.as_str()))
+            .map(|x| (x.display_name.clone(), x.device_auth.as_ref().map(|x| x.device_id.clone())))
+            .unwrap_or_default();
+
+        let mut window = Self {
+            account_id,
+            display_name,
+            current_device_id,
+            device_auths: None,
+            busy: false,
+            device_auths_communication: tokio::sync::mpsc::channel(1),
+            shared_data,
+            should_close: false,
+        };
+
+        window.reload();
+
+        window
+    }
+
+    fn create_window<'a>(&self, _ui: &egui::Ui) -> egui::Window<'a>
+    where
+        Self: Sized,
+    {
+        egui::Window::new("Device auths")
+            .resizable(false)
+            .collapsible(false)
+            .movable(false)
+            .title_bar(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+    }
+
+    fn render(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        if let Ok(device_auths) = self.device_auths_communication.1.try_recv() {
+            self.device_auths = Some(device_auths);
+            self.busy = false;
+        }
+
+        let mut delete = None;
+
+        self.create_window(ui).show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add(Label::new(rich_montserrat_text(format!("Device auths of {}", self.display_name), 18.)));
+            });
+
+            match &self.device_auths {
+                None => {
+                    ui.vertical_centered(|ui| ui.spinner());
+                }
+                Some(Err(error)) => {
+                    ui.label(error);
+                }
+                Some(Ok(device_auths)) if device_auths.is_empty() => {
+                    ui.label("Epic has no device auth for this account");
+                }
+                Some(Ok(device_auths)) => {
+                    ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                        for device_auth in device_auths {
+                            let in_use = self.current_device_id.as_deref() == Some(device_auth.device_id.as_str());
+
+                            ui.group(|ui| {
+                                ui.label(rich_montserrat_text(&device_auth.device_id, 14.));
+                                ui.label(format!("Created: {}", describe_location(&device_auth.created)));
+                                ui.label(format!("Last used: {}", describe_location(&device_auth.last_access)));
+
+                                if let Some(user_agent) = &device_auth.user_agent {
+                                    ui.label(format!("User agent: {}", user_agent));
+                                }
+
+                                if in_use {
+                                    ui.label("Used by this manager");
+                                } else if ui.add_enabled(!self.busy, egui::Button::new("Delete")).clicked() {
+                                    delete = Some(device_auth.device_id.clone());
+                                }
+                            });
+                        }
+                    });
+                }
+            }
+
+            centerer(ui, "_device_auths", |ui| {
+                if add_button(ui, "Reload", EColor::Primary).clicked() && !self.busy {
+                    self.reload();
+                }
+
+                if add_button(ui, "Close", EColor::Delete).clicked() {
+                    self.close();
+                }
+            });
+        });
+
+        if let Some(device_id) = delete {
+            self.delete(device_id);
+        }
+    }
+
+    fn close(&mut self) {
+        self.should_close = true;
+    }
+
+    fn should_appear(&self) -> bool {
+        !self.should_close
+    }
+}
@@ -4,9 +4,15 @@ use egui::{ Align2, Widget, FontSelection };
 use egui_toast::{Toast, ToastKind, ToastOptions};
 
 use crate::{
-    gui::{window::{ SubWindow, WindowDescriptor }, gui_constants::TEXT_COLOR},
-    launchers::Launchers,
+    gui::{window::{ SubWindow, WindowDescriptor }, gui_constants::{TEXT_COLOR, DELETE_COLOR}, toasts::ToToast},
+    config::Configuration,
+    game,
+    launchers::{Launcher, Launchers},
+    legendary::Legendary,
+    epic::{EpicError, EpicErrorKind},
+    secret_store::{self, SecretStoreKind},
 };
+use zeroize::Zeroize;
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub struct RuntimeSettings {
@@ -17,9 +23,128 @@ pub struct SettingsWindow {
     pub runtime_settings: Arc<Mutex<RuntimeSettings>>,
     clone_launcher: Launchers,
     clone_legendary_path: String,
+    default_legendary_path: String,
+    clone_secret_store: SecretStoreKind,
     should_close: bool,
     shared_data: crate::gui::window::WindowSharedData,
-    pub close_epic_games_launcher_on_swap_clone:bool
+    pub close_epic_games_launcher_on_swap_clone:bool,
+    clone_restart_launcher_on_swap: bool,
+    clone_launcher_executable_path: String,
+    default_launcher_executable_path: String,
+    clone_game_path: String,
+    default_game_path: String,
+    clone_auto_lock_minutes: u64,
+    master_password: String,
+    master_password_confirmation: String,
+}
+
+fn default_executable_path(configuration: &Configuration) -> String {
+    configuration.launcher
+        .resolve(configuration)
+        .ok()
+        .and_then(|launcher| launcher.executable_path())
+        .map(|path| path.display().to_string())
+        .unwrap_or_default()
+}
+
+impl SettingsWindow {
+    fn send_toast(&self, toast: Toast) {
+        let sender = self.shared_data.event_sender.clone();
+        tokio::spawn(async move {
+            let _ = sender.send(crate::gui::window::EventKind::AddToast(toast)).await;
+        });
+    }
+
+    fn refresh_current_account(&self) {
+        let account_service = self.shared_data.account_service.clone();
+        tokio::spawn(async move {
+            account_service.refresh_current_account().await;
+        });
+    }
+
+    fn success_toast(text: &str) -> Toast {
+        Toast {
+            text: text.into(),
+            kind: ToastKind::Success,
+            options: ToastOptions::default()
+                .duration_in_seconds(5.0)
+                .show_progress(true)
+                .show_icon(true),
+        }
+    }
+
+    fn render_master_password(&mut self, ui: &mut egui::Ui) {
+        if self.clone_secret_store == SecretStoreKind::MasterPassword {
+            ui.label("Your accounts are protected by a master password");
+
+            ui.horizontal(|ui| {
+                if egui::DragValue::new(&mut self.clone_auto_lock_minutes).clamp_range(0..=1440).ui(ui).changed() {
+                    let mut configuration = self.shared_data.configuration.blocking_lock();
+                    configuration.auto_lock_minutes = self.clone_auto_lock_minutes;
+                    configuration.apply_auto_lock();
+                }
+                ui.label("Auto lock after (minutes, 0 to disable)");
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("Lock now").clicked() {
+                    secret_store::lock();
+                }
+
+                if ui.button("Remove master password").clicked() {
+                    let mut configuration = self.shared_data.configuration.blocking_lock();
+
+                    match configuration.set_secret_store(SecretStoreKind::default()) {
+                        Ok(_) => {
+                            secret_store::remove_master_vault();
+                            self.clone_secret_store = configuration.secret_store;
+                            self.send_toast(Self::success_toast("Master password removed"));
+                        }
+                        Err(error) => self.send_toast(error.to_toast()),
+                    }
+                }
+            });
+
+            return;
+        }
+
+        ui.label("Protect your accounts with a master password");
+
+        egui::TextEdit::singleline(&mut self.master_password)
+            .password(true)
+            .hint_text("Master password")
+            .ui(ui);
+        egui::TextEdit::singleline(&mut self.master_password_confirmation)
+            .password(true)
+            .hint_text("Confirm master password")
+            .ui(ui);
+
+        let can_enable = !self.master_password.is_empty()
+            && self.master_password == self.master_password_confirmation;
+
+        if ui.add_enabled(can_enable, egui::Button::new("Set master password")).clicked() {
+            let result = secret_store::create_master_vault(self.master_password.as_bytes());
+            self.master_password.zeroize();
+            self.master_password_confirmation.zeroize();
+
+            let mut configuration = self.shared_data.configuration.blocking_lock();
+
+            let result = result
+                .map_err(|error| EpicError::new(EpicErrorKind::CipherError, Some(format!("Failed to create the master password vault : {}", error))))
+                .and_then(|_| configuration.set_secret_store(SecretStoreKind::MasterPassword));
+
+            match result {
+                Ok(_) => {
+                    self.clone_secret_store = SecretStoreKind::MasterPassword;
+                    self.send_toast(Self::success_toast("Master password enabled"));
+                }
+                Err(error) => {
+                    secret_store::remove_master_vault();
+                    self.send_toast(error.to_toast());
+                }
+            }
+        }
+    }
 }
 
 impl SubWindow for SettingsWindow {
@@ -33,13 +158,34 @@ impl SubWindow for SettingsWindow {
          let current_launcher = &lock.launcher;
          let current_legendary_path = &lock.legendary_path;
          let current_close_epic_games_launcher_on_swap_clone = lock.close_epic_games_launcher_on_swap;
+         let current_secret_store = lock.secret_store;
+         let current_auto_lock_minutes = lock.auto_lock_minutes;
+         let current_restart_launcher_on_swap = lock.restart_launcher_on_swap;
+         let current_launcher_executable_path = lock.launcher_executable_path.clone();
+         let default_launcher_executable_path = default_executable_path(&lock);
+         let current_game_path = lock.game_path.clone();
         SettingsWindow {
             shared_data: shared_data,
             runtime_settings: window_descriptor.runtime_settings,
             should_close: false,
             clone_launcher: current_launcher.clone(),
             clone_legendary_path: current_legendary_path.to_owned(),
-            close_epic_games_launcher_on_swap_clone:current_close_epic_games_launcher_on_swap_clone
+            default_legendary_path: Legendary::from_configuration_path("")
+                .and_then(|legendary| legendary.config_path())
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            clone_secret_store: current_secret_store,
+            close_epic_games_launcher_on_swap_clone:current_close_epic_games_launcher_on_swap_clone,
+            clone_restart_launcher_on_swap: current_restart_launcher_on_swap,
+            clone_launcher_executable_path: current_launcher_executable_path,
+            default_launcher_executable_path,
+            clone_game_path: current_game_path,
+            default_game_path: game::discover_install_path()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            clone_auto_lock_minutes: current_auto_lock_minutes,
+            master_password: String::new(),
+            master_password_confirmation: String::new(),
         }
     }
 
@@ -62,9 +208,10 @@ impl SubWindow for SettingsWindow {
         let text_size = ui.painter().layout_no_wrap(self.clone_legendary_path.clone(), font, TEXT_COLOR).size();
 
         self.create_window(ui).show(ctx, |ui| {
-            let mut runtime_settings = self.runtime_settings.lock().unwrap();
-
-            ui.checkbox(&mut runtime_settings.advanced_mode, "Advanced mode");
+            {
+                let mut runtime_settings = self.runtime_settings.lock().unwrap();
+                ui.checkbox(&mut runtime_settings.advanced_mode, "Advanced mode");
+            }
 
             if ui.checkbox(&mut self.close_epic_games_launcher_on_swap_clone, "Close EpicGames on swap").changed() {
                 //replicate to configuration
@@ -72,6 +219,25 @@ impl SubWindow for SettingsWindow {
                 configuration.close_epic_games_launcher_on_swap = self.close_epic_games_launcher_on_swap_clone;
             }
 
+            if ui.checkbox(&mut self.clone_restart_launcher_on_swap, "Restart the launcher after a swap").changed() {
+                let mut configuration = self.shared_data.configuration.blocking_lock();
+                configuration.restart_launcher_on_swap = self.clone_restart_launcher_on_swap;
+            }
+
+            if self.clone_restart_launcher_on_swap {
+                let response = egui::TextEdit::singleline(&mut self.clone_launcher_executable_path)
+                    .hint_text(&self.default_launcher_executable_path)
+                    .desired_width(text_size.x + 35.)
+                    .ui(ui)
+                    .on_hover_text("Launcher executable, the default one is used when empty");
+
+                //an empty path goes back to the discovered executable
+                if response.changed() && (self.clone_launcher_executable_path.is_empty() || PathBuf::from(&self.clone_launcher_executable_path).is_file()) {
+                    let mut configuration = self.shared_data.configuration.blocking_lock();
+                    configuration.launcher_executable_path = self.clone_launcher_executable_path.clone();
+                }
+            }
+
             egui::ComboBox
                 ::from_label("Launcher")
                 .selected_text(self.clone_launcher.to_string())
@@ -91,14 +257,65 @@ impl SubWindow for SettingsWindow {
                             {
                                 let mut configuration = self.shared_data.configuration.blocking_lock();
                                 configuration.launcher = self.clone_launcher.clone();
+                                self.default_launcher_executable_path = default_executable_path(&configuration);
+                                self.refresh_current_account();
                             }
                         });
                 });
 
+            let uses_master_password = self.clone_secret_store == SecretStoreKind::MasterPassword;
+            ui.add_enabled_ui(!uses_master_password, |ui| {
+                egui::ComboBox
+                    ::from_label("Secret storage")
+                    .selected_text(self.clone_secret_store.to_string())
+                    .show_ui(ui, |ui| {
+                        secret_store
+                            ::secret_stores()
+                            .iter()
+                            .for_each(|store| {
+                                if
+                                    ui
+                                        .selectable_value(
+                                            &mut self.clone_secret_store,
+                                            *store,
+                                            store.to_string()
+                                        )
+                                        .changed()
+                                {
+                                    let mut configuration = self.shared_data.configuration.blocking_lock();
+
+                                    let toast = match configuration.set_secret_store(self.clone_secret_store) {
+                                        Ok(_) => Toast {
+                                            text: "Secret storage updated".into(),
+                                            kind: ToastKind::Success,
+                                            options: ToastOptions::default()
+                                                .duration_in_seconds(5.0)
+                                                .show_progress(true)
+                                                .show_icon(true),
+                                        },
+                                        Err(error) => {
+                                            self.clone_secret_store = configuration.secret_store;
+                                            error.to_toast()
+                                        }
+                                    };
+
+                                    self.send_toast(toast);
+                                }
+                            });
+                    })
+                    .response
+                    .on_hover_text(self.clone_secret_store.description());
+            });
+
+            if self.clone_secret_store == SecretStoreKind::Vault {
+                ui.colored_label(DELETE_COLOR, SecretStoreKind::Vault.description());
+            }
+
             let response = egui::TextEdit::singleline(&mut self.clone_legendary_path)
+            .hint_text(&self.default_legendary_path)
             .desired_width(text_size.x + 35.)
             .ui(ui)
-            .on_hover_text("Legendary Configuration Path (not implemented)");
+            .on_hover_text("Legendary Configuration Path, the default one is used when empty");
 
             if response.double_clicked() {
                     if let Some(path) = rfd::FileDialog::new().pick_folder() {
@@ -107,6 +324,7 @@ impl SubWindow for SettingsWindow {
                             let path_str = path.display().to_string();
                             configuration.legendary_path = path_str.clone();
                             self.clone_legendary_path = path_str;
+                            self.refresh_current_account();
 
                             let sender = self.shared_data.event_sender.clone();
 
@@ -131,6 +349,7 @@ impl SubWindow for SettingsWindow {
                 if path.exists() && path.join("user.json").exists() {
                     let mut configuration = self.shared_data.configuration.blocking_lock();
                     configuration.legendary_path = self.clone_legendary_path.clone();
+                    self.refresh_current_account();
 
                     let sender = self.shared_data.event_sender.clone();
 
@@ -149,6 +368,20 @@ impl SubWindow for SettingsWindow {
                 }
             }
 
+            let response = egui::TextEdit::singleline(&mut self.clone_game_path)
+                .hint_text(&self.default_game_path)
+                .desired_width(text_size.x + 35.)
+                .ui(ui)
+                .on_hover_text("Fortnite install folder or executable used by Play as, the default one is used when empty");
+
+            if response.changed() && (self.clone_game_path.is_empty() || PathBuf::from(&self.clone_game_path).exists()) {
+                let mut configuration = self.shared_data.configuration.blocking_lock();
+                configuration.game_path = self.clone_game_path.clone();
+            }
+
+            ui.separator();
+            self.render_master_password(ui);
+
             if ui.button("Close").clicked() {
                 self.should_close = true;
             }
@@ -163,3 +396,10 @@ impl SubWindow for SettingsWindow {
         !self.should_close
     }
 }
+
+impl Drop for SettingsWindow {
+    fn drop(&mut self) {
+        self.master_password.zeroize();
+        self.master_password_confirmation.zeroize();
+    }
+}
@@ -0,0 +1,101 @@
+use egui::{Align2, Label, Widget};
+use zeroize::Zeroize;
+
+use crate::{
+    gui::{
+        gui_constants::DELETE_COLOR,
+        gui_helper::{add_button, centerer, rich_montserrat_text, EColor},
+        window::{SubWindow, WindowDescriptor, WindowSharedData},
+    },
+    secret_store::{self, SecretStoreError},
+};
+
+pub struct UnlockWindow {
+    password: String,
+    error: Option<String>,
+    should_close: bool,
+}
+
+impl UnlockWindow {
+    fn unlock(&mut self) {
+        let result = secret_store::unlock_master_vault(self.password.as_bytes());
+        self.password.zeroize();
+
+        match result {
+            Ok(_) => self.close(),
+            Err(SecretStoreError::InvalidPassphrase) => {
+                self.error = Some("Invalid master password".to_string());
+            }
+            Err(error) => {
+                self.error = Some(format!("Failed to unlock your accounts : {}", error));
+            }
+        }
+    }
+}
+
+impl SubWindow for UnlockWindow {
+    fn new(_shared_data: WindowSharedData, _window_descriptor: WindowDescriptor) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            password: String::new(),
+            error: None,
+            should_close: false,
+        }
+    }
+
+    fn create_window<'a>(&self, _ui: &egui::Ui) -> egui::Window<'a>
+    where
+        Self: Sized,
+    {
+        egui::Window::new("Unlock")
+            .resizable(false)
+            .collapsible(false)
+            .movable(false)
+            .title_bar(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+    }
+
+    fn render(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        self.create_window(ui).show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add(Label::new(rich_montserrat_text("Your accounts are locked", 18.)));
+            });
+
+            let response = egui::TextEdit::singleline(&mut self.password)
+                .password(true)
+                .hint_text("Master password")
+                .ui(ui);
+            response.request_focus();
+
+            if let Some(error) = &self.error {
+                ui.colored_label(DELETE_COLOR, error);
+            }
+
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            centerer(ui, "_unlock", |ui| {
+                if (add_button(ui, "Unlock", EColor::Primary).clicked() || submitted)
+                    && !self.password.is_empty()
+                {
+                    self.unlock();
+                }
+            });
+        });
+    }
+
+    fn close(&mut self) {
+        self.should_close = true;
+    }
+
+    fn should_appear(&self) -> bool {
+        !self.should_close
+    }
+}
+
+impl Drop for UnlockWindow {
+    fn drop(&mut self) {
+        self.password.zeroize();
+    }
+}
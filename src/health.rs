@@ -0,0 +1,54 @@
+use std::fmt::Display;
+
+use crate::epic::{error_codes, EpicError, EpicErrorKind};
+
+//what the last login with the device auth of an account told us
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize)]
+pub enum AccountHealth {
+    #[default]
+    Unknown,
+    Healthy,
+    //the password was reset or the device auth was deleted
+    Revoked,
+    Banned,
+    //the secret can't be unsealed on this machine anymore
+    CipherFailure,
+    //a corrective action like a privacy policy has to be completed first
+    ActionRequired,
+    NetworkError,
+}
+
+impl AccountHealth {
+    //the account has to be linked again, retrying won't help
+    pub fn is_broken(&self) -> bool {
+        matches!(self, AccountHealth::Revoked | AccountHealth::Banned | AccountHealth::CipherFailure)
+    }
+
+    pub fn needs_attention(&self) -> bool {
+        self.is_broken() || *self == AccountHealth::ActionRequired
+    }
+
+    pub fn classify(error: &EpicError) -> Self {
+        match (error.kind(), error.error_code()) {
+            (EpicErrorKind::CipherError, _) => AccountHealth::CipherFailure,
+            (_, Some(error_codes::ACCOUNT_NOT_ACTIVE)) => AccountHealth::Banned,
+            (_, Some(error_codes::INVALID_ACCOUNT_CREDENTIALS | error_codes::INVALID_GRANT)) => AccountHealth::Revoked,
+            (_, Some(error_codes::CORRECTIVE_ACTION_REQUIRED)) => AccountHealth::ActionRequired,
+            _ => AccountHealth::NetworkError,
+        }
+    }
+}
+
+impl Display for AccountHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccountHealth::Unknown => write!(f, "Not checked yet"),
+            AccountHealth::Healthy => write!(f, "Working"),
+            AccountHealth::Revoked => write!(f, "The device auth was revoked, link the account again"),
+            AccountHealth::Banned => write!(f, "The account is banned or disabled"),
+            AccountHealth::CipherFailure => write!(f, "The secret can't be decrypted, link the account again"),
+            AccountHealth::ActionRequired => write!(f, "Epic requires an action on this account"),
+            AccountHealth::NetworkError => write!(f, "Epic could not be reached"),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    egl::RememberMeEntry,
+    epic::{EpicError, EpicErrorKind},
+    launchers::{is_process_running, spawn_executable, stop_process, Launcher, LauncherSession, Launchers},
+    legendary::Legendary,
+};
+
+pub const HEROIC_PROCESS: &str = "heroic";
+const HEROIC_FLATPAK_ID: &str = "com.heroicgameslauncher.hgl";
+
+//heroic ships its own legendary and points it to a folder inside its configuration
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heroic {
+    path: PathBuf,
+    legendary: Legendary,
+    flatpak: bool,
+}
+
+impl Heroic {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            legendary: Legendary::new(Self::legendary_path_of(&path)),
+            flatpak: path.components().any(|x| x.as_os_str() == HEROIC_FLATPAK_ID),
+            path,
+        }
+    }
+
+    //native install first, then the flatpak sandbox
+    pub fn default_paths() -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        if let Ok(path) = std::env::var("XDG_CONFIG_HOME") {
+            paths.push(PathBuf::from(path).join("heroic"));
+        }
+
+        if let Ok(path) = std::env::var("APPDATA") {
+            paths.push(PathBuf::from(path).join("heroic"));
+        }
+
+        if let Ok(home) = std::env::var("HOME") {
+            let home = PathBuf::from(home);
+
+            paths.push(home.join(".config").join("heroic"));
+            paths.push(
+                home.join(".var")
+                    .join("app")
+                    .join(HEROIC_FLATPAK_ID)
+                    .join("config")
+                    .join("heroic"),
+            );
+            paths.push(
+                home.join("Library")
+                    .join("Application Support")
+                    .join("heroic"),
+            );
+        }
+
+        paths
+    }
+
+    //the first install with a logged in user wins, otherwise the first one that exists
+    pub fn discover() -> Result<Self, EpicError> {
+        let installs: Vec<Heroic> = Self::default_paths()
+            .into_iter()
+            .filter(|path| path.exists())
+            .map(Self::new)
+            .collect();
+
+        installs
+            .iter()
+            .find(|heroic| heroic.legendary.user_path().exists())
+            .or(installs.first())
+            .cloned()
+            .ok_or(EpicError::new(
+                EpicErrorKind::NotFound,
+                Some("Failed to find the Heroic configuration folder"),
+            ))
+    }
+
+    fn legendary_path_of(path: &Path) -> PathBuf {
+        path.join("legendaryConfig").join("legendary")
+    }
+
+    pub fn legendary_path(&self) -> PathBuf {
+        Self::legendary_path_of(&self.path)
+    }
+
+    //an empty path means heroic has to be discovered
+    pub fn from_configuration_path(path: &str) -> Result<Self, EpicError> {
+        if !path.is_empty() {
+            return Ok(Self::new(PathBuf::from(path)));
+        }
+
+        Self::discover()
+    }
+}
+
+impl Launcher for Heroic {
+    fn kind(&self) -> Launchers {
+        Launchers::Heroic
+    }
+
+    fn config_path(&self) -> Result<PathBuf, EpicError> {
+        Ok(self.path.clone())
+    }
+
+    fn read_active_account(&self) -> Result<RememberMeEntry, EpicError> {
+        self.legendary.get_remember_me_data()
+    }
+
+    fn write_active_account(&self, session: &LauncherSession) -> Result<(), EpicError> {
+        self.legendary.set_session(&session.account)
+    }
+
+    fn is_running(&self) -> bool {
+        is_process_running(HEROIC_PROCESS)
+    }
+
+    fn stop(&self) -> Result<Vec<u32>, EpicError> {
+        stop_process(HEROIC_PROCESS)
+    }
+
+    //heroic is started from the PATH or through flatpak
+    fn executable_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    fn start(&self, executable: Option<&Path>) -> Result<(), EpicError> {
+        match (executable, self.flatpak) {
+            (Some(executable), _) => spawn_executable(executable, &[]),
+            (None, true) => spawn_executable(Path::new("flatpak"), &["run", HEROIC_FLATPAK_ID]),
+            (None, false) => spawn_executable(Path::new(HEROIC_PROCESS), &[]),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    future::Future,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use lazy_static::lazy_static;
+use reqwest::{header::RETRY_AFTER, Method, RequestBuilder, Response, StatusCode};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::epic::{error_codes, EpicError, EpicErrorKind};
+
+lazy_static! {
+    static ref STATE: RwLock<HttpState> = RwLock::new(HttpState::new(HttpSettings::default()));
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HttpSettings {
+    #[serde(default = "default_request_timeout_seconds")]
+    pub request_timeout_seconds: u64,
+    #[serde(default = "default_connect_timeout_seconds")]
+    pub connect_timeout_seconds: u64,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    //first wait between two attempts, doubled on each retry
+    #[serde(default = "default_backoff_milliseconds")]
+    pub backoff_milliseconds: u64,
+    //epic asking to wait longer than this fails the request instead of blocking it
+    #[serde(default = "default_max_retry_after_seconds")]
+    pub max_retry_after_seconds: u64,
+    //requests in flight at once, shared by every account
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+}
+
+fn default_request_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_connect_timeout_seconds() -> u64 {
+    10
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_backoff_milliseconds() -> u64 {
+    500
+}
+
+fn default_max_retry_after_seconds() -> u64 {
+    60
+}
+
+fn default_max_concurrent_requests() -> usize {
+    4
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            request_timeout_seconds: default_request_timeout_seconds(),
+            connect_timeout_seconds: default_connect_timeout_seconds(),
+            max_retries: default_max_retries(),
+            backoff_milliseconds: default_backoff_milliseconds(),
+            max_retry_after_seconds: default_max_retry_after_seconds(),
+            max_concurrent_requests: default_max_concurrent_requests(),
+        }
+    }
+}
+
+impl HttpSettings {
+    pub fn max_retry_after(&self) -> Duration {
+        Duration::from_secs(self.max_retry_after_seconds)
+    }
+
+    //exponential backoff, never longer than what epic may ask for
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = Duration::from_millis(self.backoff_milliseconds).saturating_mul(2u32.saturating_pow(attempt));
+
+        delay.min(self.max_retry_after())
+    }
+}
+
+struct HttpState {
+    settings: HttpSettings,
+    client: reqwest::Client,
+    permits: Arc<Semaphore>,
+}
+
+impl HttpState {
+    fn new(settings: HttpSettings) -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(settings.connect_timeout_seconds))
+            .timeout(Duration::from_secs(settings.request_timeout_seconds))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            permits: Arc::new(Semaphore::new(settings.max_concurrent_requests.max(1))),
+            settings,
+            client,
+        }
+    }
+}
+
+pub fn client() -> reqwest::Client {
+    STATE.read().map(|x| x.client.clone()).unwrap_or_default()
+}
+
+//the requests already waiting keep the previous limit
+pub fn set_settings(settings: HttpSettings) {
+    if let Ok(mut current) = STATE.write() {
+        if current.settings != settings {
+            *current = HttpState::new(settings);
+        }
+    }
+}
+
+//a post may have been processed before failing, only retry it when epic surely did not handle it
+fn is_retryable(status: StatusCode, idempotent: bool) -> bool {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => true,
+        StatusCode::NOT_IMPLEMENTED | StatusCode::HTTP_VERSION_NOT_SUPPORTED => false,
+        status => idempotent && status.is_server_error(),
+    }
+}
+
+//only the seconds form, epic does not send dates
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+//errors.com.epicgames.common.throttled gives the seconds to wait as its first message var
+pub fn throttled_for(error: &EpicError) -> Option<Duration> {
+    match error.kind() {
+        EpicErrorKind::Api(api_error) if api_error.error_code == error_codes::THROTTLED => api_error
+            .message_vars
+            .first()
+            .and_then(|x| parse_retry_after(x)),
+        _ => None,
+    }
+}
+
+fn network_error(error: &reqwest::Error) -> EpicError {
+    if error.is_timeout() {
+        return EpicError::new(EpicErrorKind::HttpError, Some("Epic did not answer in time"));
+    }
+
+    if error.is_connect() {
+        return EpicError::new(EpicErrorKind::HttpError, Some("Failed to connect to Epic"));
+    }
+
+    EpicError::reqwest_internal_error()
+}
+
+//keeps the request in the concurrency limit until its body is read, downloads count too
+pub struct HttpResponse {
+    response: Response,
+    permit: OwnedSemaphorePermit,
+}
+
+impl HttpResponse {
+    pub fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    pub fn url(&self) -> &reqwest::Url {
+        self.response.url()
+    }
+
+    pub async fn text(self) -> reqwest::Result<String> {
+        let HttpResponse { response, permit } = self;
+        let text = response.text().await;
+        drop(permit);
+
+        text
+    }
+
+    pub async fn json<T: serde::de::DeserializeOwned>(self) -> reqwest::Result<T> {
+        let HttpResponse { response, permit } = self;
+        let data = response.json::<T>().await;
+        drop(permit);
+
+        data
+    }
+
+    pub async fn bytes(self) -> reqwest::Result<Vec<u8>> {
+        let HttpResponse { response, permit } = self;
+        let bytes = response.bytes().await.map(|x| x.to_vec());
+        drop(permit);
+
+        bytes
+    }
+}
+
+//sends the request under the global concurrency limit and retries the transient failures,
+//the response can still be an error status for the caller to handle
+pub async fn send(request: RequestBuilder) -> Result<HttpResponse, EpicError> {
+    let request = request.build().map_err(|_| EpicError::reqwest_internal_error())?;
+    let idempotent = request.method() != Method::POST;
+
+    let (settings, client, permits) = match STATE.read() {
+        Ok(state) => (state.settings.clone(), state.client.clone(), Arc::clone(&state.permits)),
+        Err(_) => return Err(EpicError::reqwest_internal_error()),
+    };
+
+    //streamed bodies can't be sent twice
+    let retries = match request.try_clone() {
+        Some(_) => settings.max_retries,
+        None => 0,
+    };
+
+    let mut request = Some(request);
+    let mut attempt = 0;
+
+    loop {
+        let current = match attempt < retries {
+            true => request.as_ref().and_then(|x| x.try_clone()),
+            false => request.take(),
+        }
+        .ok_or(EpicError::reqwest_internal_error())?;
+
+        let permit = Arc::clone(&permits)
+            .acquire_owned()
+            .await
+            .map_err(|_| EpicError::reqwest_internal_error())?;
+
+        let (error, delay) = match client.execute(current).await {
+            Ok(response) if !is_retryable(response.status(), idempotent) => return Ok(HttpResponse { response, permit }),
+            Ok(response) => {
+                let status = response.status();
+                let retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|x| x.to_str().ok())
+                    .and_then(parse_retry_after);
+
+                let body = response.text().await.unwrap_or_default();
+                let error = EpicError::response_error(status, &body);
+                let delay = retry_after
+                    .or(throttled_for(&error))
+                    .unwrap_or(settings.backoff(attempt));
+
+                (error, delay)
+            }
+            Err(error) if error.is_connect() || idempotent => (network_error(&error), settings.backoff(attempt)),
+            Err(error) => return Err(network_error(&error)),
+        };
+
+        if attempt >= retries || delay > settings.max_retry_after() {
+            return Err(error);
+        }
+
+        //other requests can go while this one waits
+        drop(permit);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+//lets the request builders of epic.rs end with .send_with_retries() instead of .send()
+pub trait SendWithRetries {
+    fn send_with_retries(self) -> impl Future<Output = Result<HttpResponse, EpicError>>;
+}
+
+impl SendWithRetries for RequestBuilder {
+    fn send_with_retries(self) -> impl Future<Output = Result<HttpResponse, EpicError>> {
+        send(self)
+    }
+}
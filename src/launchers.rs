@@ -1,9 +1,22 @@
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::Configuration,
+    egl::{EpicGamesLauncher, RememberMeEntry},
+    epic::{EpicAccount, EpicAccountDetails, EpicError, EpicErrorKind},
+    heroic::Heroic,
+    legendary::Legendary,
+    process::{self, ProcessError},
+};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Launchers {
     EpicGamesLauncher,
-    Legendary
+    Legendary,
+    Heroic
 }
 
 impl Default for Launchers {
@@ -12,8 +25,8 @@ impl Default for Launchers {
     }
 }  
 
-pub fn launchers() -> [Launchers; 2] {
-    [Launchers::EpicGamesLauncher, Launchers::Legendary]
+pub fn launchers() -> [Launchers; 3] {
+    [Launchers::EpicGamesLauncher, Launchers::Legendary, Launchers::Heroic]
 
 }
 
@@ -22,6 +35,7 @@ impl Into<String> for Launchers {
         match self {
             Launchers::EpicGamesLauncher => String::from("EpicGamesLauncher"),
             Launchers::Legendary => String::from("Legendary"),
+            Launchers::Heroic => String::from("Heroic"),
         }
     }
 }
@@ -39,7 +53,8 @@ impl serde::Serialize for Launchers {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
         match self {
             Launchers::EpicGamesLauncher => serializer.serialize_str("EpicGamesLauncher"),
-            Launchers::Legendary => serializer.serialize_str("Legendary")
+            Launchers::Legendary => serializer.serialize_str("Legendary"),
+            Launchers::Heroic => serializer.serialize_str("Heroic")
         }
     }
 }
@@ -51,7 +66,81 @@ impl<'de> serde::Deserialize<'de> for Launchers {
         match launcher.as_str() {
             "EpicGamesLauncher" => Ok(Launchers::EpicGamesLauncher),
             "Legendary" => Ok(Launchers::Legendary),
+            "Heroic" => Ok(Launchers::Heroic),
             _ => Err(serde::de::Error::custom("Invalid launcher"))
         }
     }
-}
\ No newline at end of file
+}
+
+//what a launcher needs to log an account in, the details are fetched once by the caller
+pub struct LauncherSession {
+    pub account: EpicAccount,
+    pub details: EpicAccountDetails,
+}
+
+pub trait Launcher: Send + Sync {
+    fn kind(&self) -> Launchers;
+
+    //folder or file the launcher keeps its session in
+    fn config_path(&self) -> Result<PathBuf, EpicError>;
+
+    fn read_active_account(&self) -> Result<RememberMeEntry, EpicError>;
+    fn write_active_account(&self, session: &LauncherSession) -> Result<(), EpicError>;
+
+    fn is_running(&self) -> bool;
+
+    //asks the launcher to close, the returned processes are the ones to wait for
+    fn stop(&self) -> Result<Vec<u32>, EpicError>;
+
+    //executable started when the configuration has none, none when the launcher starts another way
+    fn executable_path(&self) -> Option<PathBuf>;
+    fn start(&self, executable: Option<&Path>) -> Result<(), EpicError>;
+}
+
+impl Launchers {
+    pub fn resolve(&self, configuration: &Configuration) -> Result<Box<dyn Launcher>, EpicError> {
+        match self {
+            Launchers::EpicGamesLauncher => Ok(Box::new(EpicGamesLauncher)),
+            Launchers::Legendary => Ok(Box::new(Legendary::from_configuration_path(&configuration.legendary_path)?)),
+            Launchers::Heroic => Ok(Box::new(Heroic::from_configuration_path(&configuration.heroic_path)?)),
+        }
+    }
+}
+
+pub(crate) fn is_process_running(process_name: &str) -> bool {
+    process::is_running(process_name)
+}
+
+fn process_error(process_name: &str, error: ProcessError) -> EpicError {
+    let kind = match error {
+        ProcessError::NotFound(_) => EpicErrorKind::NotFound,
+        _ => EpicErrorKind::Other,
+    };
+
+    EpicError::new(kind, Some(format!("Failed to stop {} : {}", process_name, error)))
+}
+
+pub(crate) fn stop_process(process_name: &str) -> Result<Vec<u32>, EpicError> {
+    process::request_stop(process_name).map_err(|error| process_error(process_name, error))
+}
+
+//waits up to GRACEFUL_STOP_TIMEOUT, the processes ignoring the close request are killed
+pub async fn wait_for_exit(pids: Vec<u32>, name: &str) -> Result<(), EpicError> {
+    tokio::task::spawn_blocking(move || process::wait_or_kill(&pids, process::GRACEFUL_STOP_TIMEOUT))
+        .await
+        .map_err(|_| EpicError::new(EpicErrorKind::Other, Some(format!("Failed to wait for {}", name))))?
+        .map_err(|error| process_error(name, error))
+}
+
+pub(crate) fn spawn_executable(executable: &Path, args: &[&str]) -> Result<(), EpicError> {
+    std::process::Command::new(executable)
+        .args(args)
+        .spawn()
+        .map(|_| ())
+        .map_err(|_| {
+            EpicError::new(
+                EpicErrorKind::IoError,
+                Some(format!("Failed to start {}", executable.display())),
+            )
+        })
+}
@@ -1,23 +1,212 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::egl::RememberMeEntry;
+use crate::{
+    egl::RememberMeEntry,
+    epic::{EpicAccount, EpicError, EpicErrorKind},
+    launchers::{is_process_running, stop_process, Launcher, LauncherSession, Launchers},
+};
 
+//overrides the folder legendary reads its configuration from, same as legendary itself
+pub const LEGENDARY_CONFIG_PATH_ENV_VAR: &str = "LEGENDARY_CONFIG_PATH";
 
+//the session saved by legendary, it is the token response of launcherAppClient2
+#[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LegendaryUserData {
+    pub access_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    #[serde(default)]
+    pub token_type: String,
+    pub refresh_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_expires: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_expires_at: Option<String>,
+    pub account_id: String,
+    pub client_id: String,
+    #[serde(default)]
+    pub internal_client: bool,
+    #[serde(default)]
+    pub client_service: String,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    #[serde(default)]
+    pub app: String,
+    #[serde(default)]
+    pub in_app_id: String,
+    //fields we don't know about are kept as is
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl LegendaryUserData {
+    pub fn from_epic_account(account: &EpicAccount) -> Result<Self, EpicError> {
+        let missing = |field: &str| {
+            EpicError::new(
+                EpicErrorKind::InvalidResponse,
+                Some(format!("The session has no {}", field)),
+            )
+        };
+
+        let account_id = account.account_id.clone().ok_or(missing("account_id"))?;
+        let tokens = account.tokens();
+
+        Ok(Self {
+            access_token: tokens.access_token,
+            expires_in: tokens.expires_in,
+            expires_at: tokens.expires_at,
+            token_type: "bearer".to_string(),
+            refresh_token: tokens.refresh_token.ok_or(missing("refresh_token"))?,
+            refresh_expires: tokens.refresh_expires,
+            refresh_expires_at: tokens.refresh_expires_at,
+            in_app_id: account_id.clone(),
+            account_id,
+            client_id: account.client_id.clone(),
+            internal_client: true,
+            client_service: "launcher".to_string(),
+            display_name: account.display_name.clone().ok_or(missing("displayName"))?,
+            app: "launcher".to_string(),
+            extra: serde_json::Map::new(),
+        })
+    }
+
+    pub fn to_remember_me_entry(&self) -> RememberMeEntry {
+        RememberMeEntry {
+            region: "Prod".to_string(),
+            display_name: self.display_name.clone(),
+            token: self.refresh_token.clone(),
+            ..RememberMeEntry::default()
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq)]
-struct Legendary {
-    path:PathBuf
+pub struct Legendary {
+    path: PathBuf,
 }
 
 impl Legendary {
-    #[allow(dead_code)]
-    pub fn new(path:PathBuf) -> Self {
-        Self {
-            path
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    //same lookup order as legendary : env var, then $XDG_CONFIG_HOME, then ~/.config
+    pub fn default_path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var(LEGENDARY_CONFIG_PATH_ENV_VAR) {
+            return Some(PathBuf::from(path));
+        }
+
+        if let Ok(path) = std::env::var("XDG_CONFIG_HOME") {
+            return Some(PathBuf::from(path).join("legendary"));
+        }
+
+        std::env::var("HOME")
+            .or(std::env::var("USERPROFILE"))
+            .ok()
+            .map(|home| PathBuf::from(home).join(".config").join("legendary"))
+    }
+
+    //an empty path means the user did not pick a folder in the settings
+    pub fn from_configuration_path(path: &str) -> Result<Self, EpicError> {
+        if !path.is_empty() {
+            return Ok(Self::new(PathBuf::from(path)));
         }
+
+        Self::default_path().map(Self::new).ok_or(EpicError::new(
+            EpicErrorKind::NotFound,
+            Some("Failed to find the Legendary configuration folder"),
+        ))
+    }
+
+    pub fn user_path(&self) -> PathBuf {
+        self.path.join("user.json")
+    }
+
+    pub fn get_user_data(&self) -> Result<LegendaryUserData, EpicError> {
+        let data = std::fs::read_to_string(self.user_path()).map_err(|_| {
+            EpicError::new(
+                EpicErrorKind::IoError,
+                Some("Failed to read Legendary user.json"),
+            )
+        })?;
+
+        serde_json::from_str(&data).map_err(|_| {
+            EpicError::new(
+                EpicErrorKind::ParsingError,
+                Some("Failed to parse Legendary user.json"),
+            )
+        })
+    }
+
+    pub fn set_user_data(&self, data: &LegendaryUserData) -> Result<(), EpicError> {
+        std::fs::create_dir_all(&self.path).map_err(|_| {
+            EpicError::new(
+                EpicErrorKind::IoError,
+                Some("Failed to create the Legendary configuration folder"),
+            )
+        })?;
+
+        let json = serde_json::to_string_pretty(data).map_err(|_| {
+            EpicError::new(
+                EpicErrorKind::EncodingError,
+                Some("Failed to encode Legendary user.json"),
+            )
+        })?;
+
+        std::fs::write(self.user_path(), json).map_err(|_| {
+            EpicError::new(
+                EpicErrorKind::IoError,
+                Some("Failed to write Legendary user.json"),
+            )
+        })
+    }
+
+    pub fn get_remember_me_data(&self) -> Result<RememberMeEntry, EpicError> {
+        Ok(self.get_user_data()?.to_remember_me_entry())
+    }
+
+    //the account must be logged in with launcherAppClient2, legendary refreshes the session with it
+    pub fn set_session(&self, account: &EpicAccount) -> Result<(), EpicError> {
+        self.set_user_data(&LegendaryUserData::from_epic_account(account)?)
+    }
+}
+
+//legendary is a command line tool, it only runs while a command is in progress
+pub const LEGENDARY_PROCESS: &str = "legendary";
+
+impl Launcher for Legendary {
+    fn kind(&self) -> Launchers {
+        Launchers::Legendary
+    }
+
+    fn config_path(&self) -> Result<PathBuf, EpicError> {
+        Ok(self.path.clone())
+    }
+
+    fn read_active_account(&self) -> Result<RememberMeEntry, EpicError> {
+        self.get_remember_me_data()
+    }
+
+    fn write_active_account(&self, session: &LauncherSession) -> Result<(), EpicError> {
+        self.set_session(&session.account)
+    }
+
+    fn is_running(&self) -> bool {
+        is_process_running(LEGENDARY_PROCESS)
+    }
+
+    fn stop(&self) -> Result<Vec<u32>, EpicError> {
+        stop_process(LEGENDARY_PROCESS)
     }
 
-    pub fn get_remember_me_data() -> RememberMeEntry {
-        todo!()
+    fn executable_path(&self) -> Option<PathBuf> {
+        None
     }
-}
\ No newline at end of file
+
+    //nothing stays in the background, the next legendary command reads user.json again
+    fn start(&self, _executable: Option<&Path>) -> Result<(), EpicError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,293 @@
+use std::{sync::Arc, time::Duration};
+
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use reqwest::Url;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+
+use crate::{
+    epic::{EpicError, EpicErrorKind},
+    epic_clients::AuthClient,
+};
+
+const EPIC_WEBSITE: &str = "https://www.epicgames.com";
+
+//the user has this long to log in before the listener gives up
+pub const LOGIN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+//a browser request line and its headers, the body is never read
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+
+//browsers open connections ahead of time and leave them idle
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RedirectResponse {
+    #[serde(default)]
+    authorization_code: Option<String>,
+    #[serde(default)]
+    redirect_url: Option<String>,
+}
+
+//the page epic sends game clients to after the login, its JSON holds the authorization code
+pub fn login_url(client: &AuthClient) -> String {
+    let redirect = format!(
+        "{}/id/api/redirect?clientId={}&responseType=code",
+        EPIC_WEBSITE, client.id
+    );
+
+    Url::parse_with_params(&format!("{}/id/login", EPIC_WEBSITE), &[("redirectUrl", redirect)])
+        .map(|x| x.to_string())
+        .unwrap_or_default()
+}
+
+//only works with clients allowing a redirect to 127.0.0.1, the game clients don't
+pub fn authorize_url(client: &AuthClient, redirect_uri: &str, state: &str) -> String {
+    Url::parse_with_params(
+        &format!("{}/id/authorize", EPIC_WEBSITE),
+        &[
+            ("client_id", client.id),
+            ("response_type", "code"),
+            ("redirect_uri", redirect_uri),
+            ("state", state),
+        ],
+    )
+    .map(|x| x.to_string())
+    .unwrap_or_default()
+}
+
+//accepts the JSON of /id/api/redirect, the redirect url it contains or the bare code
+pub fn extract_code(text: &str) -> Option<String> {
+    let text = text.trim().trim_matches('"');
+
+    if let Ok(response) = serde_json::from_str::<RedirectResponse>(text) {
+        return response
+            .authorization_code
+            .or(response.redirect_url.as_deref().and_then(extract_code));
+    }
+
+    if let Ok(url) = Url::parse(text) {
+        return url
+            .query_pairs()
+            .find(|(name, _)| name == "code")
+            .map(|(_, code)| code.to_string());
+    }
+
+    match !text.is_empty() && text.chars().all(|x| x.is_ascii_alphanumeric()) {
+        true => Some(text.to_string()),
+        false => None,
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn random_state() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+enum Page {
+    Guide(Option<&'static str>),
+    Done,
+    Failed(String),
+    NotFound,
+}
+
+//serves the page guiding the user through the login and receives the authorization code,
+//either from epic redirecting the browser back or from the JSON the user pastes in the page
+pub struct LoopbackListener {
+    listener: TcpListener,
+    page: Arc<LoginPage>,
+}
+
+struct LoginPage {
+    login_url: String,
+    //the requests of other pages don't know it
+    state: String,
+}
+
+impl LoopbackListener {
+    //must be called inside the tokio runtime
+    pub fn bind(client: &AuthClient) -> Result<Self, EpicError> {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0))
+            .and_then(|x| x.set_nonblocking(true).map(|_| x))
+            .and_then(TcpListener::from_std)
+            .map_err(|error| {
+                EpicError::new(
+                    EpicErrorKind::Other,
+                    Some(format!("Failed to start the local login page : {}", error)),
+                )
+            })?;
+
+        Ok(Self {
+            listener,
+            page: Arc::new(LoginPage {
+                login_url: login_url(client),
+                state: random_state(),
+            }),
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.listener.local_addr().map(|x| x.port()).unwrap_or_default()
+    }
+
+    pub fn state(&self) -> &str {
+        &self.page.state
+    }
+
+    //the guide page, also where epic redirects when it allows it
+    pub fn local_url(&self) -> String {
+        format!("http://127.0.0.1:{}/", self.port())
+    }
+
+    //every connection is handled on its own, a silent one can't hold back the others
+    pub async fn wait_for_code(&self) -> Result<String, EpicError> {
+        let (sender, mut receiver) = mpsc::channel(1);
+
+        loop {
+            tokio::select! {
+                accepted = self.listener.accept() => {
+                    let Ok((stream, _)) = accepted else {
+                        continue;
+                    };
+
+                    let page = Arc::clone(&self.page);
+                    let sender = sender.clone();
+
+                    tokio::spawn(async move {
+                        if let Some(result) = page.handle(stream).await {
+                            let _ = sender.send(result).await;
+                        }
+                    });
+                }
+                Some(result) = receiver.recv() => return result,
+            }
+        }
+    }
+}
+
+impl LoginPage {
+    async fn handle(&self, mut stream: TcpStream) -> Option<Result<String, EpicError>> {
+        let target = tokio::time::timeout(REQUEST_TIMEOUT, read_request_target(&mut stream))
+            .await
+            .ok()??;
+        let url = Url::parse(&format!("http://127.0.0.1{}", target)).ok()?;
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.to_string())
+        };
+
+        let (page, result) = match (url.path(), param("state")) {
+            ("/", None) => (Page::Guide(None), None),
+            ("/", Some(state)) if state != self.state => (Page::Guide(Some("This login was started by another page")), None),
+            ("/", Some(_)) => match (param("code"), param("value"), param("error")) {
+                (Some(code), _, _) => (Page::Done, Some(Ok(code))),
+                (_, _, Some(error)) => {
+                    let message = param("error_description").unwrap_or(error);
+                    (
+                        Page::Failed(message.clone()),
+                        Some(Err(EpicError::new(EpicErrorKind::Other, Some(format!("Epic refused the login : {}", message))))),
+                    )
+                }
+                (_, Some(value), _) => match extract_code(&value) {
+                    Some(code) => (Page::Done, Some(Ok(code))),
+                    None => (Page::Guide(Some("No authorization code was found in what you pasted")), None),
+                },
+                _ => (Page::Guide(None), None),
+            },
+            _ => (Page::NotFound, None),
+        };
+
+        let _ = self.respond(&mut stream, page).await;
+
+        result
+    }
+
+    async fn respond(&self, stream: &mut TcpStream, page: Page) -> std::io::Result<()> {
+        let (status, body) = match page {
+            Page::Guide(error) => ("200 OK", self.guide_page(error)),
+            Page::Done => ("200 OK", html_page("<p>The account is being added, you can close this tab.</p>".to_string())),
+            Page::Failed(message) => ("200 OK", html_page(format!("<p>The login failed : {}</p>", escape_html(&message)))),
+            Page::NotFound => ("404 Not Found", String::new()),
+        };
+
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    fn guide_page(&self, error: Option<&str>) -> String {
+        let error = error
+            .map(|x| format!("<p class=\"error\">{}</p>", escape_html(x)))
+            .unwrap_or_default();
+
+        html_page(format!(
+            "{}\
+            <ol>\
+            <li><a href=\"{}\" target=\"_blank\">Log in to Epic Games</a> with the account to add.</li>\
+            <li>Once logged in, Epic shows a JSON page, copy all of it.</li>\
+            <li>Paste it below.</li>\
+            </ol>\
+            <form method=\"get\" action=\"/\">\
+            <input type=\"hidden\" name=\"state\" value=\"{}\">\
+            <textarea name=\"value\" rows=\"6\" cols=\"80\"></textarea><br>\
+            <button type=\"submit\">Add the account</button>\
+            </form>",
+            error,
+            escape_html(&self.login_url),
+            escape_html(&self.state)
+        ))
+    }
+}
+
+fn html_page(content: String) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Alt Manager login</title>\
+        <style>body{{font-family:sans-serif;margin:2em}}.error{{color:#d9534f}}</style></head>\
+        <body><h1>Add an account to Alt Manager</h1>{}</body></html>",
+        content
+    )
+}
+
+//the path and query of the request line, "GET /?code=... HTTP/1.1"
+async fn read_request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+
+    while !request.windows(4).any(|x| x == b"\r\n\r\n") && request.len() < MAX_REQUEST_SIZE {
+        let read = stream.read(&mut buffer).await.ok()?;
+
+        if read == 0 {
+            break;
+        }
+
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next()?.split_whitespace();
+
+    match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some(target)) if target.starts_with('/') => Some(target.to_string()),
+        _ => None,
+    }
+}
@@ -6,21 +6,39 @@ use crate::egl::get_decryption_keys;
 use egui::{Color32, Rounding, Stroke, Style, Visuals};
 use gui::gui_constants::{MODAL_COLOR, TEXT_COLOR};
 
+mod accounts;
+mod cli;
 mod config;
 mod decrypt;
 mod egl;
+mod endpoints;
 mod epic;
 mod gui;
 mod process;
+mod secret_store;
 mod version;
 mod legendary;
+mod heroic;
 mod launchers;
 mod epic_clients;
+mod game;
+mod health;
+mod http;
+mod loopback;
+
+#[cfg(test)]
+mod tests;
 
 use eframe::{egui, NativeOptions};
 use tokio::runtime::Runtime;
 
 fn main() {
+    //any argument switches to the headless cli
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
     let rt = Runtime::new().expect("Unable to create Runtime");
 
     let _enter = rt.enter();
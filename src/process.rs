@@ -1,47 +1,313 @@
-use windows::Win32::{
-    Foundation::CloseHandle,
-    System::{
-        Diagnostics::ToolHelp::{
-            CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
-            TH32CS_SNAPPROCESS,
-        },
-        Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE},
-    },
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
 };
 
-pub unsafe fn get_process_pid(name: String) -> Result<u32, Box<dyn std::error::Error>> {
-    let handle = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0)?;
+//time left to a process to exit after being asked politely, then after being killed
+pub const GRACEFUL_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+pub const FORCED_STOP_TIMEOUT: Duration = Duration::from_secs(3);
 
-    let mut process_entry: PROCESSENTRY32W = std::mem::zeroed();
-    process_entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
 
-    Process32FirstW(handle, &mut process_entry as *mut PROCESSENTRY32W)?;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessError {
+    NotFound(String),
+    AccessDenied(u32),
+    Timeout(u32),
+    Os(String),
+}
 
-    while Process32NextW(handle, &mut process_entry as *mut PROCESSENTRY32W).is_ok() {
-        let process_name = String::from_utf16(&process_entry.szExeFile)?;
-        if process_name.contains(&name) {
-            CloseHandle(handle)?;
-            return Ok(process_entry.th32ProcessID);
+impl Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessError::NotFound(name) => write!(f, "No process named {} is running", name),
+            ProcessError::AccessDenied(pid) => write!(f, "Access denied to process {}", pid),
+            ProcessError::Timeout(pid) => write!(f, "Process {} did not exit in time", pid),
+            ProcessError::Os(message) => write!(f, "{}", message),
         }
     }
+}
+
+impl std::error::Error for ProcessError {}
 
-    return Err("failed to find process".into());
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub parent_pid: u32,
+    pub executable: String,
 }
 
-pub unsafe fn kill_process(pid: u32) -> Result<(), Box<dyn std::error::Error>> {
-    let h_process = OpenProcess(PROCESS_TERMINATE, false, pid)?;
-    TerminateProcess(h_process, 0)?;
-    CloseHandle(h_process)?;
+//compares executable names the same way on every platform : no folder, no case, no .exe
+pub fn matches_name(executable: &str, name: &str) -> bool {
+    fn normalize(value: &str) -> String {
+        let file_name = value.rsplit(['/', '\\']).next().unwrap_or(value).to_lowercase();
+
+        match file_name.strip_suffix(".exe") {
+            Some(stem) => stem.to_string(),
+            None => file_name,
+        }
+    }
+
+    !name.is_empty() && normalize(executable) == normalize(name)
+}
+
+pub fn list_processes() -> Result<Vec<ProcessInfo>, ProcessError> {
+    platform::list_processes()
+}
+
+pub fn find_processes(name: &str) -> Result<Vec<u32>, ProcessError> {
+    let pids: Vec<u32> = list_processes()?
+        .into_iter()
+        .filter(|process| matches_name(&process.executable, name))
+        .map(|process| process.pid)
+        .collect();
+
+    match pids.is_empty() {
+        true => Err(ProcessError::NotFound(name.to_string())),
+        false => Ok(pids),
+    }
+}
+
+pub fn is_running(name: &str) -> bool {
+    find_processes(name).is_ok()
+}
+
+pub fn is_alive(pid: u32) -> bool {
+    platform::is_alive(pid)
+}
+
+//a graceful termination asks the process to close, a forced one kills it
+pub fn terminate(pid: u32, force: bool) -> Result<(), ProcessError> {
+    platform::terminate(pid, force)
+}
+
+pub fn wait_for_exit(pid: u32, timeout: Duration) -> Result<(), ProcessError> {
+    let deadline = Instant::now() + timeout;
+
+    while is_alive(pid) {
+        if Instant::now() >= deadline {
+            return Err(ProcessError::Timeout(pid));
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
 
     Ok(())
 }
 
-pub fn kill_epic_games_launcher() -> Result<(), Box<dyn std::error::Error>> {
-    unsafe {
-        let pid = get_process_pid("EpicGamesLauncher.exe".to_string())?;
+//asks every process with this name to close and returns the ones to wait for
+pub fn request_stop(name: &str) -> Result<Vec<u32>, ProcessError> {
+    let pids = find_processes(name)?;
+
+    for pid in pids.iter() {
+        //processes without a window can't be closed gracefully, they are killed by wait_or_kill
+        let _ = terminate(*pid, false);
+    }
+
+    Ok(pids)
+}
+
+//the processes still alive once the timeout is over are killed
+pub fn wait_or_kill(pids: &[u32], timeout: Duration) -> Result<(), ProcessError> {
+    let deadline = Instant::now() + timeout;
 
-        kill_process(pid)?;
+    for pid in pids.iter() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+
+        if wait_for_exit(*pid, remaining).is_err() {
+            terminate(*pid, true)?;
+            wait_for_exit(*pid, FORCED_STOP_TIMEOUT)?;
+        }
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows::Win32::{
+        Foundation::{
+            CloseHandle, BOOL, ERROR_ACCESS_DENIED, HANDLE, HWND, LPARAM, WAIT_TIMEOUT, WPARAM,
+        },
+        System::{
+            Diagnostics::ToolHelp::{
+                CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+                TH32CS_SNAPPROCESS,
+            },
+            Threading::{
+                OpenProcess, TerminateProcess, WaitForSingleObject, PROCESS_ACCESS_RIGHTS,
+                PROCESS_SYNCHRONIZE, PROCESS_TERMINATE,
+            },
+        },
+        UI::WindowsAndMessaging::{EnumWindows, GetWindowThreadProcessId, PostMessageW, WM_CLOSE},
+    };
+
+    use super::{ProcessError, ProcessInfo};
+
+    //closes the handle on every return path
+    struct OwnedHandle(HANDLE);
+
+    impl Drop for OwnedHandle {
+        fn drop(&mut self) {
+            let _ = unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    fn open(pid: u32, access: PROCESS_ACCESS_RIGHTS) -> Result<OwnedHandle, ProcessError> {
+        unsafe { OpenProcess(access, false, pid) }
+            .map(OwnedHandle)
+            .map_err(|error| match error.code() == ERROR_ACCESS_DENIED.to_hresult() {
+                true => ProcessError::AccessDenied(pid),
+                false => ProcessError::Os(error.message().to_string()),
+            })
+    }
+
+    pub fn list_processes() -> Result<Vec<ProcessInfo>, ProcessError> {
+        let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) }
+            .map(OwnedHandle)
+            .map_err(|error| ProcessError::Os(error.message().to_string()))?;
+
+        let mut entry: PROCESSENTRY32W = unsafe { std::mem::zeroed() };
+        entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+
+        let mut processes = Vec::new();
+        let mut next = unsafe { Process32FirstW(snapshot.0, &mut entry) };
+
+        while next.is_ok() {
+            let length = entry.szExeFile.iter().position(|x| *x == 0).unwrap_or(entry.szExeFile.len());
+            processes.push(ProcessInfo {
+                pid: entry.th32ProcessID,
+                parent_pid: entry.th32ParentProcessID,
+                executable: String::from_utf16_lossy(&entry.szExeFile[..length]),
+            });
+
+            next = unsafe { Process32NextW(snapshot.0, &mut entry) };
+        }
+
+        Ok(processes)
+    }
+
+    pub fn is_alive(pid: u32) -> bool {
+        match open(pid, PROCESS_SYNCHRONIZE) {
+            Ok(handle) => unsafe { WaitForSingleObject(handle.0, 0) == WAIT_TIMEOUT },
+            //the process exists but belongs to someone else
+            Err(ProcessError::AccessDenied(_)) => true,
+            Err(_) => false,
+        }
+    }
+
+    unsafe extern "system" fn close_windows_of(window: HWND, pid: LPARAM) -> BOOL {
+        let mut window_pid = 0u32;
+        GetWindowThreadProcessId(window, Some(&mut window_pid));
+
+        if window_pid == pid.0 as u32 {
+            let _ = PostMessageW(window, WM_CLOSE, WPARAM(0), LPARAM(0));
+        }
+
+        true.into()
+    }
+
+    pub fn terminate(pid: u32, force: bool) -> Result<(), ProcessError> {
+        if !force {
+            return unsafe { EnumWindows(Some(close_windows_of), LPARAM(pid as isize)) }
+                .map_err(|error| ProcessError::Os(error.message().to_string()));
+        }
+
+        let handle = open(pid, PROCESS_TERMINATE)?;
+        unsafe { TerminateProcess(handle.0, 1) }.map_err(|error| {
+            match error.code() == ERROR_ACCESS_DENIED.to_hresult() {
+                true => ProcessError::AccessDenied(pid),
+                false => ProcessError::Os(error.message().to_string()),
+            }
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::path::Path;
+
+    use super::{ProcessError, ProcessInfo};
+
+    //argv[0] keeps the full name, comm is cut at 15 characters
+    fn executable_of(process: &Path) -> Option<String> {
+        let cmdline = std::fs::read(process.join("cmdline")).ok()?;
+        let argv0 = cmdline.split(|x| *x == 0).next().unwrap_or_default();
+
+        if !argv0.is_empty() {
+            return Some(String::from_utf8_lossy(argv0).to_string());
+        }
+
+        std::fs::read_to_string(process.join("comm"))
+            .ok()
+            .map(|comm| comm.trim_end().to_string())
+    }
+
+    //the fields following the executable name, which can contain spaces and parenthesis
+    fn stat_fields(pid: u32) -> Option<Vec<String>> {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        let (_, fields) = stat.rsplit_once(')')?;
+
+        Some(fields.split_whitespace().map(String::from).collect())
+    }
+
+    pub fn list_processes() -> Result<Vec<ProcessInfo>, ProcessError> {
+        let entries = std::fs::read_dir("/proc").map_err(|error| ProcessError::Os(error.to_string()))?;
+
+        Ok(entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+
+                Some(ProcessInfo {
+                    pid,
+                    parent_pid: stat_fields(pid)?.get(1)?.parse().ok()?,
+                    executable: executable_of(&entry.path())?,
+                })
+            })
+            .collect())
+    }
+
+    //a zombie has exited, it only waits for its parent to read the exit code
+    pub fn is_alive(pid: u32) -> bool {
+        stat_fields(pid)
+            .and_then(|fields| fields.first().cloned())
+            .is_some_and(|state| state != "Z" && state != "X")
+    }
+
+    pub fn terminate(pid: u32, force: bool) -> Result<(), ProcessError> {
+        let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+
+        if unsafe { libc::kill(pid as libc::pid_t, signal) } == 0 {
+            return Ok(());
+        }
+
+        let error = std::io::Error::last_os_error();
+        match error.raw_os_error() {
+            Some(libc::EPERM) => Err(ProcessError::AccessDenied(pid)),
+            Some(libc::ESRCH) => Ok(()),
+            _ => Err(ProcessError::Os(error.to_string())),
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod platform {
+    use super::{ProcessError, ProcessInfo};
+
+    fn unsupported() -> ProcessError {
+        ProcessError::Os("Process management is not supported on this platform".to_string())
+    }
+
+    pub fn list_processes() -> Result<Vec<ProcessInfo>, ProcessError> {
+        Err(unsupported())
+    }
+
+    pub fn is_alive(_pid: u32) -> bool {
+        false
+    }
+
+    pub fn terminate(_pid: u32, _force: bool) -> Result<(), ProcessError> {
+        Err(unsupported())
+    }
+}
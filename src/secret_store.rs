@@ -0,0 +1,625 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose, Engine};
+use lazy_static::lazy_static;
+use zeroize::Zeroizing;
+
+//takes precedence over the generated vault.key file
+pub const VAULT_PASSPHRASE_ENV_VAR: &str = "ALT_MANAGER_VAULT_PASSPHRASE";
+
+const VAULT_VERSION: u32 = 1;
+const VAULT_CHECK: &[u8] = b"alt-manager-vault";
+const NONCE_SIZE: usize = 12;
+const SALT_SIZE: usize = 16;
+
+lazy_static! {
+    static ref STORES: RwLock<SecretStores> = RwLock::new(SecretStores {
+        active: SecretStoreKind::default(),
+        vault: None,
+        master: None,
+        auto_lock_after: None,
+        last_activity: Instant::now(),
+    });
+}
+
+struct SecretStores {
+    active: SecretStoreKind,
+    vault: Option<Arc<FileVault>>,
+    master: Option<Arc<FileVault>>,
+    auto_lock_after: Option<Duration>,
+    last_activity: Instant,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretStoreError {
+    Unsupported,
+    Locked,
+    InvalidPassphrase,
+    Corrupted,
+    IoError,
+    CipherError,
+}
+
+impl Display for SecretStoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SecretStoreError::Unsupported => "Unsupported",
+                SecretStoreError::Locked => "Locked",
+                SecretStoreError::InvalidPassphrase => "InvalidPassphrase",
+                SecretStoreError::Corrupted => "Corrupted",
+                SecretStoreError::IoError => "IoError",
+                SecretStoreError::CipherError => "CipherError",
+            }
+        )?;
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for SecretStoreError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum SecretStoreKind {
+    Dpapi,
+    Vault,
+    MasterPassword,
+}
+
+impl Default for SecretStoreKind {
+    fn default() -> Self {
+        if cfg!(windows) {
+            SecretStoreKind::Dpapi
+        } else {
+            SecretStoreKind::Vault
+        }
+    }
+}
+
+impl Display for SecretStoreKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SecretStoreKind::Dpapi => "Windows DPAPI",
+                SecretStoreKind::Vault => "Local key file",
+                SecretStoreKind::MasterPassword => "Master password",
+            }
+        )?;
+
+        Ok(())
+    }
+}
+
+impl SecretStoreKind {
+    //what the user is told about the protection each store gives
+    pub fn description(&self) -> &'static str {
+        match self {
+            SecretStoreKind::Dpapi => "Only this Windows user on this computer can read your accounts",
+            SecretStoreKind::Vault => {
+                "The key is saved next to your accounts, it only hides them from a quick look, use a master password to protect them"
+            }
+            SecretStoreKind::MasterPassword => "Your accounts can't be read without your master password",
+        }
+    }
+
+    //prefix of every sealed blob, tells which store has to open it
+    fn tag(&self) -> &'static str {
+        match self {
+            SecretStoreKind::Dpapi => "dpapi",
+            SecretStoreKind::Vault => "vault",
+            SecretStoreKind::MasterPassword => "master",
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "dpapi" => Some(SecretStoreKind::Dpapi),
+            "vault" => Some(SecretStoreKind::Vault),
+            "master" => Some(SecretStoreKind::MasterPassword),
+            _ => None,
+        }
+    }
+}
+
+//stores selectable without a password, the master password has its own controls
+pub fn secret_stores() -> Vec<SecretStoreKind> {
+    if cfg!(windows) {
+        vec![SecretStoreKind::Dpapi, SecretStoreKind::Vault]
+    } else {
+        vec![SecretStoreKind::Vault]
+    }
+}
+
+pub trait SecretStore: Send + Sync {
+    fn kind(&self) -> SecretStoreKind;
+    fn seal(&self, data: &[u8]) -> Result<Vec<u8>, SecretStoreError>;
+    fn unseal(&self, data: &[u8]) -> Result<Vec<u8>, SecretStoreError>;
+}
+
+#[cfg(windows)]
+pub struct DpapiStore;
+
+#[cfg(windows)]
+impl SecretStore for DpapiStore {
+    fn kind(&self) -> SecretStoreKind {
+        SecretStoreKind::Dpapi
+    }
+
+    fn seal(&self, data: &[u8]) -> Result<Vec<u8>, SecretStoreError> {
+        use windows::{
+            core::PCWSTR,
+            Win32::{
+                Foundation::{LocalFree, HLOCAL},
+                Security::Cryptography::{
+                    CryptProtectData, CRYPTPROTECT_LOCAL_MACHINE, CRYPT_INTEGER_BLOB,
+                },
+            },
+        };
+
+        let in_blob = CRYPT_INTEGER_BLOB {
+            cbData: data.len() as u32,
+            pbData: data.as_ptr() as *mut u8,
+        };
+
+        let mut out_blob = unsafe { std::mem::zeroed::<CRYPT_INTEGER_BLOB>() };
+
+        unsafe {
+            CryptProtectData(
+                &in_blob,
+                PCWSTR::null(),
+                None,
+                None,
+                None,
+                CRYPTPROTECT_LOCAL_MACHINE,
+                &mut out_blob,
+            )
+        }
+        .map_err(|_| SecretStoreError::CipherError)?;
+
+        let sealed =
+            unsafe { std::slice::from_raw_parts(out_blob.pbData, out_blob.cbData as usize) }.to_vec();
+        let _ = unsafe { LocalFree(HLOCAL(out_blob.pbData as *mut _)) };
+
+        Ok(sealed)
+    }
+
+    fn unseal(&self, data: &[u8]) -> Result<Vec<u8>, SecretStoreError> {
+        use windows::Win32::{
+            Foundation::{LocalFree, HLOCAL},
+            Security::Cryptography::{
+                CryptUnprotectData, CRYPTPROTECT_LOCAL_MACHINE, CRYPT_INTEGER_BLOB,
+            },
+        };
+
+        let in_blob = CRYPT_INTEGER_BLOB {
+            cbData: data.len() as u32,
+            pbData: data.as_ptr() as *mut u8,
+        };
+
+        let mut out_blob = unsafe { std::mem::zeroed::<CRYPT_INTEGER_BLOB>() };
+
+        unsafe {
+            CryptUnprotectData(
+                &in_blob,
+                None,
+                None,
+                None,
+                None,
+                CRYPTPROTECT_LOCAL_MACHINE,
+                &mut out_blob,
+            )
+        }
+        .map_err(|_| SecretStoreError::CipherError)?;
+
+        let secret =
+            unsafe { std::slice::from_raw_parts(out_blob.pbData, out_blob.cbData as usize) }.to_vec();
+        let _ = unsafe { LocalFree(HLOCAL(out_blob.pbData as *mut _)) };
+
+        Ok(secret)
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct VaultHeader {
+    version: u32,
+    salt: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    //VAULT_CHECK sealed with the derived key, used to reject a wrong passphrase
+    check: String,
+}
+
+//AES-256-GCM vault, the key is derived from a passphrase with Argon2id
+pub struct FileVault {
+    kind: SecretStoreKind,
+    header: VaultHeader,
+    key: RwLock<Option<Zeroizing<[u8; 32]>>>,
+}
+
+impl FileVault {
+    pub fn open_or_create(path: &Path, passphrase: &[u8]) -> Result<Self, SecretStoreError> {
+        if path.exists() {
+            let vault = Self::open(path, SecretStoreKind::Vault)?;
+            vault.unlock(passphrase)?;
+            return Ok(vault);
+        }
+
+        Self::create(path, passphrase, SecretStoreKind::Vault)
+    }
+
+    //overwrites any vault previously stored at this path
+    pub fn create(path: &Path, passphrase: &[u8], kind: SecretStoreKind) -> Result<Self, SecretStoreError> {
+        let mut salt = [0u8; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+
+        let params = Params::default();
+        let mut header = VaultHeader {
+            version: VAULT_VERSION,
+            salt: general_purpose::STANDARD.encode(salt),
+            m_cost: params.m_cost(),
+            t_cost: params.t_cost(),
+            p_cost: params.p_cost(),
+            check: String::new(),
+        };
+
+        let key = Self::derive_key(&header, passphrase)?;
+        header.check = general_purpose::STANDARD.encode(encrypt(&key, VAULT_CHECK)?);
+
+        let data = serde_json::to_string_pretty(&header).map_err(|_| SecretStoreError::Corrupted)?;
+        std::fs::write(path, data).map_err(|_| SecretStoreError::IoError)?;
+
+        Ok(Self {
+            kind,
+            header,
+            key: RwLock::new(Some(key)),
+        })
+    }
+
+    //the vault stays locked until unlock is called
+    pub fn open(path: &Path, kind: SecretStoreKind) -> Result<Self, SecretStoreError> {
+        let data = std::fs::read_to_string(path).map_err(|_| SecretStoreError::IoError)?;
+        let header: VaultHeader = serde_json::from_str(&data).map_err(|_| SecretStoreError::Corrupted)?;
+
+        if header.version != VAULT_VERSION {
+            return Err(SecretStoreError::Unsupported);
+        }
+
+        Ok(Self {
+            kind,
+            header,
+            key: RwLock::new(None),
+        })
+    }
+
+    pub fn unlock(&self, passphrase: &[u8]) -> Result<(), SecretStoreError> {
+        let key = Self::derive_key(&self.header, passphrase)?;
+        let check = general_purpose::STANDARD
+            .decode(&self.header.check)
+            .map_err(|_| SecretStoreError::Corrupted)?;
+
+        if decrypt(&key, &check).map_err(|_| SecretStoreError::InvalidPassphrase)? != VAULT_CHECK {
+            return Err(SecretStoreError::InvalidPassphrase);
+        }
+
+        *self.key.write().map_err(|_| SecretStoreError::Locked)? = Some(key);
+        Ok(())
+    }
+
+    //the key is zeroized when dropped
+    pub fn lock(&self) {
+        if let Ok(mut key) = self.key.write() {
+            *key = None;
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.key.read().map(|x| x.is_none()).unwrap_or(true)
+    }
+
+    fn derive_key(header: &VaultHeader, passphrase: &[u8]) -> Result<Zeroizing<[u8; 32]>, SecretStoreError> {
+        let salt = general_purpose::STANDARD
+            .decode(&header.salt)
+            .map_err(|_| SecretStoreError::Corrupted)?;
+        let params = Params::new(header.m_cost, header.t_cost, header.p_cost, Some(32))
+            .map_err(|_| SecretStoreError::Corrupted)?;
+
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase, &salt, key.as_mut())
+            .map_err(|_| SecretStoreError::CipherError)?;
+
+        Ok(key)
+    }
+}
+
+impl SecretStore for FileVault {
+    fn kind(&self) -> SecretStoreKind {
+        self.kind
+    }
+
+    fn seal(&self, data: &[u8]) -> Result<Vec<u8>, SecretStoreError> {
+        let key = self.key.read().map_err(|_| SecretStoreError::Locked)?;
+        encrypt(key.as_ref().ok_or(SecretStoreError::Locked)?, data)
+    }
+
+    fn unseal(&self, data: &[u8]) -> Result<Vec<u8>, SecretStoreError> {
+        let key = self.key.read().map_err(|_| SecretStoreError::Locked)?;
+        decrypt(key.as_ref().ok_or(SecretStoreError::Locked)?, data)
+    }
+}
+
+//output is nonce || ciphertext
+fn encrypt(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, SecretStoreError> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(
+        cipher
+            .encrypt(&nonce, data)
+            .map_err(|_| SecretStoreError::CipherError)?,
+    );
+
+    Ok(sealed)
+}
+
+fn decrypt(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, SecretStoreError> {
+    if data.len() < NONCE_SIZE {
+        return Err(SecretStoreError::Corrupted);
+    }
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let (nonce, ciphertext) = data.split_at(NONCE_SIZE);
+
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| SecretStoreError::CipherError)
+}
+
+fn vault_path() -> PathBuf {
+    crate::config::Configuration::get_path().with_file_name("vault.json")
+}
+
+//without a passphrase in the environment, a random one is kept next to the vault,
+//whoever can read the configuration folder can open the vault
+fn automatic_passphrase(vault_path: &Path) -> Result<Zeroizing<Vec<u8>>, SecretStoreError> {
+    if let Ok(passphrase) = std::env::var(VAULT_PASSPHRASE_ENV_VAR) {
+        if !passphrase.is_empty() {
+            return Ok(Zeroizing::new(passphrase.into_bytes()));
+        }
+    }
+
+    let key_path = vault_path.with_file_name("vault.key");
+
+    if key_path.exists() {
+        let passphrase = std::fs::read_to_string(&key_path).map_err(|_| SecretStoreError::IoError)?;
+        return Ok(Zeroizing::new(passphrase.trim().as_bytes().to_vec()));
+    }
+
+    let mut random = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(random.as_mut());
+    let passphrase = Zeroizing::new(
+        random
+            .iter()
+            .map(|x| format!("{:02x}", x))
+            .collect::<String>(),
+    );
+
+    write_private_file(&key_path, passphrase.as_bytes()).map_err(|_| SecretStoreError::IoError)?;
+
+    Ok(Zeroizing::new(passphrase.as_bytes().to_vec()))
+}
+
+//never overwrites an existing file, it could be readable by others
+pub(crate) fn write_private_file(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options.open(path)?.write_all(data)
+}
+
+pub fn vault() -> Result<Arc<FileVault>, SecretStoreError> {
+    if let Some(vault) = STORES.read().map_err(|_| SecretStoreError::Locked)?.vault.clone() {
+        return Ok(vault);
+    }
+
+    let mut stores = STORES.write().map_err(|_| SecretStoreError::Locked)?;
+
+    //another thread may have opened it while we were waiting for the lock
+    if let Some(vault) = stores.vault.clone() {
+        return Ok(vault);
+    }
+
+    let path = vault_path();
+    let passphrase = automatic_passphrase(&path)?;
+    let vault = Arc::new(FileVault::open_or_create(&path, &passphrase)?);
+
+    stores.vault = Some(Arc::clone(&vault));
+    Ok(vault)
+}
+
+fn master_vault_path() -> PathBuf {
+    crate::config::Configuration::get_path().with_file_name("master_vault.json")
+}
+
+//opened locked, nothing can be unsealed before the user types the master password
+pub fn master_vault() -> Result<Arc<FileVault>, SecretStoreError> {
+    if let Some(master) = STORES.read().map_err(|_| SecretStoreError::Locked)?.master.clone() {
+        return Ok(master);
+    }
+
+    let mut stores = STORES.write().map_err(|_| SecretStoreError::Locked)?;
+
+    if let Some(master) = stores.master.clone() {
+        return Ok(master);
+    }
+
+    let master = Arc::new(FileVault::open(&master_vault_path(), SecretStoreKind::MasterPassword)?);
+
+    stores.master = Some(Arc::clone(&master));
+    Ok(master)
+}
+
+pub fn create_master_vault(password: &[u8]) -> Result<(), SecretStoreError> {
+    let master = FileVault::create(&master_vault_path(), password, SecretStoreKind::MasterPassword)?;
+
+    let mut stores = STORES.write().map_err(|_| SecretStoreError::Locked)?;
+    stores.master = Some(Arc::new(master));
+    stores.last_activity = Instant::now();
+
+    Ok(())
+}
+
+pub fn unlock_master_vault(password: &[u8]) -> Result<(), SecretStoreError> {
+    master_vault()?.unlock(password)?;
+    touch();
+
+    Ok(())
+}
+
+pub fn remove_master_vault() {
+    if let Ok(mut stores) = STORES.write() {
+        if let Some(master) = stores.master.take() {
+            master.lock();
+        }
+    }
+
+    let _ = std::fs::remove_file(master_vault_path());
+}
+
+pub fn lock() {
+    if let Ok(stores) = STORES.read() {
+        if let Some(master) = &stores.master {
+            master.lock();
+        }
+    }
+}
+
+//only the master password vault needs the user, the other stores unlock themselves
+pub fn is_locked() -> bool {
+    if active_store() != SecretStoreKind::MasterPassword {
+        return false;
+    }
+
+    master_vault().map(|x| x.is_locked()).unwrap_or(true)
+}
+
+pub fn set_auto_lock_after(duration: Option<Duration>) {
+    if let Ok(mut stores) = STORES.write() {
+        stores.auto_lock_after = duration;
+    }
+}
+
+//called on user activity to push back the auto lock
+pub fn touch() {
+    if let Ok(mut stores) = STORES.write() {
+        stores.last_activity = Instant::now();
+    }
+}
+
+//returns true when the vault has just been locked
+pub fn lock_if_idle() -> bool {
+    let should_lock = match STORES.read() {
+        Ok(stores) => {
+            stores.active == SecretStoreKind::MasterPassword
+                && stores.auto_lock_after.is_some_and(|x| stores.last_activity.elapsed() >= x)
+                && stores.master.as_ref().is_some_and(|x| !x.is_locked())
+        }
+        Err(_) => false,
+    };
+
+    if should_lock {
+        lock();
+    }
+
+    should_lock
+}
+
+fn store(kind: SecretStoreKind) -> Result<Arc<dyn SecretStore>, SecretStoreError> {
+    match kind {
+        #[cfg(windows)]
+        SecretStoreKind::Dpapi => Ok(Arc::new(DpapiStore)),
+        #[cfg(not(windows))]
+        SecretStoreKind::Dpapi => Err(SecretStoreError::Unsupported),
+        SecretStoreKind::Vault => Ok(vault()?),
+        SecretStoreKind::MasterPassword => Ok(master_vault()?),
+    }
+}
+
+pub fn set_active_store(kind: SecretStoreKind) {
+    if let Ok(mut stores) = STORES.write() {
+        stores.active = kind;
+    }
+}
+
+pub fn active_store() -> SecretStoreKind {
+    STORES.read().map(|x| x.active).unwrap_or_default()
+}
+
+//blobs written before the secret stores existed are untagged base64 DPAPI output
+fn parse_sealed(sealed: &str) -> Result<(SecretStoreKind, Vec<u8>), SecretStoreError> {
+    let (kind, data) = match sealed.split_once(':') {
+        Some((tag, data)) => (
+            SecretStoreKind::from_tag(tag).ok_or(SecretStoreError::Unsupported)?,
+            data,
+        ),
+        None => (SecretStoreKind::Dpapi, sealed),
+    };
+
+    let data = general_purpose::STANDARD
+        .decode(data)
+        .map_err(|_| SecretStoreError::Corrupted)?;
+
+    Ok((kind, data))
+}
+
+pub fn seal(secret: &[u8]) -> Result<String, SecretStoreError> {
+    seal_in(active_store(), secret)
+}
+
+pub fn seal_in(kind: SecretStoreKind, secret: &[u8]) -> Result<String, SecretStoreError> {
+    let store = store(kind)?;
+    let sealed = store.seal(secret)?;
+
+    Ok(format!("{}:{}", store.kind().tag(), general_purpose::STANDARD.encode(sealed)))
+}
+
+pub fn unseal(sealed: &str) -> Result<Zeroizing<Vec<u8>>, SecretStoreError> {
+    let (kind, data) = parse_sealed(sealed)?;
+    Ok(Zeroizing::new(store(kind)?.unseal(&data)?))
+}
+
+//true when the blob has to be resealed to end up in the active store
+pub fn needs_migration(sealed: &str) -> bool {
+    needs_migration_to(sealed, active_store())
+}
+
+pub fn needs_migration_to(sealed: &str, kind: SecretStoreKind) -> bool {
+    match sealed.split_once(':') {
+        Some((tag, _)) => SecretStoreKind::from_tag(tag) != Some(kind),
+        None => true,
+    }
+}
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::{
+    accounts::{AccountService, RemovalMode},
+    cli::{self, CliError, Command},
+    config::Configuration,
+};
+
+use super::workflows::add_mock_account;
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|x| x.to_string()).collect()
+}
+
+#[test]
+fn parses_account_commands() {
+    assert_eq!(cli::parse(&args(&["list"])), Ok(Command::List));
+    assert_eq!(
+        cli::parse(&args(&["add", "--browser"])),
+        Ok(Command::AddBrowser("launcherAppClient2".to_string()))
+    );
+    assert_eq!(cli::parse(&args(&["swap", "Alt"])), Ok(Command::Swap("Alt".to_string())));
+    assert_eq!(cli::parse(&args(&["play", "Alt"])), Ok(Command::Play("Alt".to_string())));
+    assert_eq!(cli::parse(&args(&["refresh"])), Ok(Command::Refresh));
+    assert_eq!(cli::parse(&args(&["check"])), Ok(Command::Check));
+    assert_eq!(cli::parse(&args(&["logout", "Alt"])), Ok(Command::Logout("Alt".to_string())));
+    assert_eq!(
+        cli::parse(&args(&["remove", "Alt"])),
+        Ok(Command::Remove("Alt".to_string(), RemovalMode::RevokeOnEpic))
+    );
+    assert_eq!(
+        cli::parse(&args(&["remove", "Alt", "--local-only"])),
+        Ok(Command::Remove("Alt".to_string(), RemovalMode::LocalOnly))
+    );
+    assert_eq!(cli::parse(&args(&["device-auths", "Alt"])), Ok(Command::DeviceAuths("Alt".to_string())));
+    assert_eq!(
+        cli::parse(&args(&["delete-replaced-device-auths"])),
+        Ok(Command::DeleteReplacedDeviceAuths)
+    );
+    assert_eq!(
+        cli::parse(&args(&["delete-device-auth", "Alt", "device"])),
+        Ok(Command::DeleteDeviceAuth("Alt".to_string(), "device".to_string()))
+    );
+    assert_eq!(
+        cli::parse(&args(&["clone-settings", "Main", "Alt"])),
+        Ok(Command::CloneSettings("Main".to_string(), "Alt".to_string()))
+    );
+    assert_eq!(
+        cli::parse(&args(&["export", "--output", "accounts.json"])),
+        Ok(Command::Export(Some("accounts.json".into())))
+    );
+}
+
+#[test]
+fn parses_add_with_device_auth() {
+    let command = cli::parse(&args(&[
+        "add",
+        "--device-auth",
+        "--account-id",
+        "account",
+        "--device-id",
+        "device",
+        "--secret",
+        "secret",
+    ]));
+
+    assert_eq!(
+        command,
+        Ok(Command::AddDeviceAuth {
+            account_id: "account".to_string(),
+            device_id: "device".to_string(),
+            secret: "secret".to_string(),
+            client: "fortniteIOSGameClient".to_string(),
+        })
+    );
+}
+
+#[test]
+fn rejects_invalid_arguments() {
+    assert_eq!(cli::parse(&args(&["swap"])), Err(CliError::MissingArgument("<name>")));
+    assert_eq!(
+        cli::parse(&args(&["remove", "Alt", "Main"])),
+        Err(CliError::UnexpectedArgument("Main".to_string()))
+    );
+    assert_eq!(
+        cli::parse(&args(&["add", "--device-auth", "--account-id", "account"])),
+        Err(CliError::MissingArgument("--device-id"))
+    );
+    assert_eq!(
+        cli::parse(&args(&["login"])),
+        Err(CliError::UnknownCommand("login".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn export_unseals_device_auths() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "ExportUser").await;
+
+    let secret = descriptor.device_auth.as_ref().unwrap().secret.clone();
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+
+    let exported = account_service.export_accounts().await.unwrap();
+
+    assert_eq!(exported.len(), 1);
+    assert_eq!(exported[0].display_name, "ExportUser");
+    assert_ne!(exported[0].secret, secret);
+
+    let server_account = super::environment()
+        .server
+        .account(&exported[0].account_id)
+        .unwrap();
+    assert_eq!(server_account.device_auths[0].secret, exported[0].secret);
+}
@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use reqwest::StatusCode;
+use tokio::sync::Mutex;
+
+use crate::{
+    accounts::{AccountOutcome, AccountService, RemovalMode},
+    config::{AddAccountProvider, Configuration},
+    epic::{self, Token},
+};
+
+use super::{environment, launcher_client, workflows::add_mock_account};
+
+fn device_ids(account_id: &str) -> Vec<String> {
+    environment()
+        .server
+        .account(account_id)
+        .unwrap()
+        .device_auths
+        .iter()
+        .map(|x| x.device_id.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn device_auths_are_listed() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "ListDeviceAuthsUser").await;
+    let device_auth = descriptor.device_auth.clone().unwrap();
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    let device_auths = account_service.device_auths(&device_auth.account_id).await.unwrap();
+
+    assert_eq!(device_auths.len(), 1);
+    assert_eq!(device_auths[0].device_id, device_auth.device_id);
+    assert_eq!(device_auths[0].account_id, device_auth.account_id);
+    assert_eq!(
+        device_auths[0].created.as_ref().and_then(|x| x.location.as_deref()),
+        Some("Localhost")
+    );
+}
+
+#[tokio::test]
+async fn other_device_auths_can_be_deleted() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "DeleteDeviceAuthUser").await;
+    let mut device_auth = descriptor.device_auth.clone().unwrap();
+    let account_id = device_auth.account_id.clone();
+
+    let other = device_auth
+        .login()
+        .await
+        .unwrap()
+        .create_device_auth()
+        .await
+        .unwrap();
+    assert_eq!(device_ids(&account_id).len(), 2);
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    let outcome = account_service.delete_device_auth(&account_id, &other.device_id).await.unwrap();
+
+    assert!(matches!(outcome, AccountOutcome::DeviceAuthDeleted(_)));
+    assert_eq!(device_ids(&account_id), vec![device_auth.device_id.clone()]);
+}
+
+#[tokio::test]
+async fn the_device_auth_in_use_is_not_deleted() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "KeepDeviceAuthUser").await;
+    let device_auth = descriptor.device_auth.clone().unwrap();
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+
+    assert!(account_service
+        .delete_device_auth(&device_auth.account_id, &device_auth.device_id)
+        .await
+        .is_err());
+    assert_eq!(device_ids(&device_auth.account_id), vec![device_auth.device_id.clone()]);
+}
+
+#[tokio::test]
+async fn linking_an_account_again_deletes_its_previous_device_auth() {
+    let mut configuration = Configuration::default();
+    let first = add_mock_account(&mut configuration, "RelinkedUser").await;
+    let account_id = first.account_id().unwrap().to_string();
+
+    let refresh_token = environment()
+        .server
+        .create_refresh_token(&account_id, launcher_client().id);
+    let epic_account = epic::token(Token::RefreshToken(&refresh_token), launcher_client())
+        .await
+        .unwrap();
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    let outcome = account_service.add_epic_account(&epic_account).await.unwrap();
+    let AccountOutcome::Added(second) = outcome else {
+        panic!("Unexpected outcome {:?}", outcome);
+    };
+
+    let device_id = second.device_auth.as_ref().unwrap().device_id.clone();
+    assert_ne!(device_id, first.device_auth.as_ref().unwrap().device_id);
+    assert_eq!(device_ids(&account_id), vec![device_id]);
+    assert_eq!(account_service.configuration().lock().await.accounts.len(), 1);
+}
+
+#[tokio::test]
+async fn the_configuration_keeps_the_device_auth_it_replaces() {
+    let mut configuration = Configuration::default();
+    let first = add_mock_account(&mut configuration, "ReplacedUntilSavedUser").await;
+    let account_id = first.account_id().unwrap().to_string();
+
+    let refresh_token = environment()
+        .server
+        .create_refresh_token(&account_id, launcher_client().id);
+    let epic_account = epic::token(Token::RefreshToken(&refresh_token), launcher_client())
+        .await
+        .unwrap();
+    let (_, superseded) = configuration
+        .add_account(AddAccountProvider::EpicAccount(&epic_account))
+        .await
+        .unwrap();
+
+    //deleted by the account service once the configuration is saved
+    assert_eq!(superseded, Some(first.device_auth.as_ref().unwrap().device_id.clone()));
+    assert_eq!(device_ids(&account_id).len(), 2);
+}
+
+#[tokio::test]
+async fn device_auths_replaced_by_duplicates_are_deleted_when_asked() {
+    let mut configuration = Configuration::default();
+    let first = add_mock_account(&mut configuration, "DuplicatedDeviceAuthUser").await;
+    let account_id = first.account_id().unwrap().to_string();
+
+    //what older versions saved after linking the account again under another name
+    let mut device_auth = first
+        .device_auth
+        .clone()
+        .unwrap()
+        .login()
+        .await
+        .unwrap()
+        .create_device_auth()
+        .await
+        .unwrap();
+    device_auth.cipher_secret().unwrap();
+    let device_id = device_auth.device_id.clone();
+
+    let mut relinked = first.clone();
+    relinked.display_name = "RelinkedDuplicatedDeviceAuthUser".to_string();
+    relinked.device_auth = Some(device_auth);
+    configuration.accounts.push(relinked);
+
+    configuration.deduplicate_accounts();
+    assert_eq!(configuration.superseded_device_auths.len(), 1);
+    assert_eq!(device_ids(&account_id).len(), 2);
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    let outcome = account_service.delete_superseded_device_auths().await.unwrap();
+
+    assert!(matches!(outcome, AccountOutcome::SupersededDeviceAuthsDeleted { deleted: 1, failed: 0 }));
+    assert_eq!(device_ids(&account_id), vec![device_id]);
+    assert!(account_service.configuration().lock().await.superseded_device_auths.is_empty());
+}
+
+#[tokio::test]
+async fn removed_accounts_lose_their_device_auth() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "RemovedDeviceAuthUser").await;
+    let account_id = descriptor.account_id().unwrap().to_string();
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    account_service.remove_account(&account_id, RemovalMode::RevokeOnEpic).await.unwrap();
+
+    assert!(device_ids(&account_id).is_empty());
+    assert!(account_service.configuration().lock().await.accounts.is_empty());
+}
+
+#[tokio::test]
+async fn accounts_can_be_removed_locally_only() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "LocallyRemovedUser").await;
+    let account_id = descriptor.account_id().unwrap().to_string();
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    let outcome = account_service.remove_account(&account_id, RemovalMode::LocalOnly).await.unwrap();
+
+    assert!(matches!(outcome, AccountOutcome::RemovedLocally(_)));
+    assert_eq!(device_ids(&account_id).len(), 1);
+    assert!(account_service.configuration().lock().await.accounts.is_empty());
+}
+
+#[tokio::test]
+async fn accounts_with_revoked_credentials_are_removed_locally() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "AlreadyRevokedUser").await;
+    let account_id = descriptor.account_id().unwrap().to_string();
+    environment().server.revoke_device_auths(&account_id);
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    let outcome = account_service.remove_account(&account_id, RemovalMode::RevokeOnEpic).await.unwrap();
+
+    assert!(matches!(outcome, AccountOutcome::RemovedLocally(_)));
+    assert!(account_service.configuration().lock().await.accounts.is_empty());
+}
+
+#[tokio::test]
+async fn accounts_with_undecryptable_secrets_are_kept() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "UndecryptableUser").await;
+    let account_id = descriptor.account_id().unwrap().to_string();
+    configuration.accounts[0].device_auth.as_mut().unwrap().secret = "corrupted".to_string();
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+
+    assert!(account_service.remove_account(&account_id, RemovalMode::RevokeOnEpic).await.is_err());
+    assert_eq!(account_service.configuration().lock().await.accounts.len(), 1);
+    assert_eq!(device_ids(&account_id).len(), 1);
+
+    let outcome = account_service.remove_account(&account_id, RemovalMode::LocalOnly).await.unwrap();
+    assert!(matches!(outcome, AccountOutcome::RemovedLocally(_)));
+}
+
+#[tokio::test]
+async fn accounts_are_kept_when_epic_fails_to_revoke() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "UnrevokedUser").await;
+    let account_id = descriptor.account_id().unwrap().to_string();
+    environment()
+        .server
+        .fail_next_requests(&account_id, 10, StatusCode::INTERNAL_SERVER_ERROR);
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+
+    assert!(account_service.remove_account(&account_id, RemovalMode::RevokeOnEpic).await.is_err());
+    assert_eq!(account_service.configuration().lock().await.accounts.len(), 1);
+}
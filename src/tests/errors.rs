@@ -0,0 +1,80 @@
+use reqwest::StatusCode;
+
+use crate::epic::{self, error_codes, CorrectiveAction, EpicError, EpicErrorKind, Token};
+
+use super::{environment, launcher_client};
+
+#[test]
+fn error_envelopes_are_parsed() {
+    let body = r#"{
+        "errorCode": "errors.com.epicgames.common.throttled",
+        "errorMessage": "Operation access is limited by throttling policy, please try again in 12 second(s).",
+        "messageVars": ["12"],
+        "numericErrorCode": 1041,
+        "originatingService": "com.epicgames.account.public",
+        "intent": "prod"
+    }"#;
+
+    let error = EpicError::response_error(StatusCode::TOO_MANY_REQUESTS, body);
+    let EpicErrorKind::Api(api_error) = error.kind() else {
+        panic!("{:?} is not an api error", error);
+    };
+
+    assert_eq!(api_error.status, 429);
+    assert_eq!(api_error.numeric_error_code, 1041);
+    assert_eq!(api_error.message_vars, vec!["12".to_string()]);
+    assert_eq!(error.error_code(), Some(error_codes::THROTTLED));
+    assert_eq!(
+        error.to_string(),
+        "Too many requests, try again in 12 seconds (errors.com.epicgames.common.throttled)"
+    );
+}
+
+#[test]
+fn unknown_codes_keep_the_epic_message() {
+    let body = r#"{"errorCode": "errors.com.epicgames.common.server_error", "errorMessage": "Sorry an error occurred"}"#;
+    let error = EpicError::response_error(StatusCode::INTERNAL_SERVER_ERROR, body);
+
+    assert_eq!(error.to_string(), "Sorry an error occurred (errors.com.epicgames.common.server_error)");
+}
+
+#[test]
+fn bodies_without_an_envelope_keep_the_status() {
+    let error = EpicError::response_error(StatusCode::BAD_GATEWAY, "<html>Bad Gateway</html>");
+
+    assert!(matches!(error.kind(), EpicErrorKind::InvalidResponse));
+    assert_eq!(error.error_code(), None);
+    assert!(error.to_string().contains("502"));
+}
+
+#[test]
+fn corrective_actions_keep_their_continuation() {
+    let body = r#"{
+        "errorCode": "errors.com.epicgames.oauth.corrective_action_required",
+        "errorMessage": "Corrective action is required to continue.",
+        "numericErrorCode": 18206,
+        "correctiveAction": "PRIVACY_POLICY_ACCEPTANCE",
+        "continuation": "continuation-token"
+    }"#;
+
+    let error = EpicError::response_error(StatusCode::BAD_REQUEST, body);
+
+    assert_eq!(
+        error.corrective_action(),
+        Some((CorrectiveAction::PrivacyPolicyAcceptance, "continuation-token"))
+    );
+    assert!(error.to_string().contains("accepting the updated privacy policy"));
+    assert_eq!(EpicError::response_error(StatusCode::BAD_REQUEST, "{}").corrective_action(), None);
+}
+
+#[tokio::test]
+async fn failed_logins_report_the_epic_error() {
+    environment();
+
+    let error = epic::token(Token::RefreshToken("invalid"), launcher_client())
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.error_code(), Some(error_codes::INVALID_GRANT));
+    assert!(error.to_string().starts_with("The login was refused"));
+}
@@ -0,0 +1,168 @@
+use std::path::PathBuf;
+
+#[cfg(unix)]
+use std::{path::Path, sync::Arc};
+
+#[cfg(unix)]
+use tokio::sync::Mutex;
+
+use crate::game::{self, executable_from_path, fortnite_arguments, legendary_install_path, split_arguments};
+
+#[cfg(unix)]
+use crate::{
+    accounts::{AccountEvent, AccountOutcome, AccountService},
+    config::Configuration,
+    epic::AccountDescriptor,
+};
+
+use super::test_folder;
+
+#[cfg(unix)]
+use super::{environment, workflows::add_mock_account};
+
+#[test]
+fn extra_arguments_are_split_like_a_shell() {
+    assert_eq!(split_arguments(""), Vec::<String>::new());
+    assert_eq!(split_arguments("  -nosound   -windowed "), vec!["-nosound", "-windowed"]);
+    assert_eq!(
+        split_arguments("-log \"-path=C:\\My Games\" \"\""),
+        vec!["-log", "-path=C:\\My Games", ""]
+    );
+}
+
+#[test]
+fn fortnite_is_given_the_exchange_code() {
+    let arguments = fortnite_arguments("code", "accountid", "Player", "-nosound");
+
+    assert!(arguments.contains(&"-AUTH_TYPE=exchangecode".to_string()));
+    assert!(arguments.contains(&"-AUTH_PASSWORD=code".to_string()));
+    assert!(arguments.contains(&"-epicuserid=accountid".to_string()));
+    assert!(arguments.contains(&"-epicusername=Player".to_string()));
+    assert_eq!(arguments.last().map(|x| x.as_str()), Some("-nosound"));
+}
+
+#[test]
+fn install_folders_resolve_to_the_launcher_executable() {
+    let folder = test_folder("game", "install");
+
+    assert_eq!(
+        executable_from_path(&folder),
+        folder.join("FortniteGame").join("Binaries").join("Win64").join(game::FORTNITE_EXECUTABLE)
+    );
+
+    let executable = folder.join("custom.exe");
+    assert_eq!(executable_from_path(&executable), executable);
+}
+
+#[test]
+fn legendary_installs_are_read_from_installed_json() {
+    let folder = test_folder("game", "legendary");
+    std::fs::write(
+        folder.join("installed.json"),
+        r#"{"Fortnite": {"app_name": "Fortnite", "install_path": "/games/Fortnite", "version": "1"}}"#,
+    )
+    .unwrap();
+
+    assert_eq!(legendary_install_path(&folder), Some(PathBuf::from("/games/Fortnite")));
+    assert_eq!(legendary_install_path(&test_folder("game", "empty")), None);
+}
+
+//stands in for the game executable
+#[cfg(unix)]
+fn fake_game(folder: &Path, script: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let executable = folder.join("fortnite.sh");
+    std::fs::write(&executable, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    executable
+}
+
+#[cfg(unix)]
+async fn game_service(executable: &Path, accounts: &[&str]) -> (AccountService, Vec<AccountDescriptor>) {
+    let mut configuration = Configuration::default();
+    configuration.game_path = executable.display().to_string();
+
+    let mut descriptors = Vec::new();
+    for account in accounts {
+        descriptors.push(add_mock_account(&mut configuration, account).await);
+    }
+
+    (AccountService::new(Arc::new(Mutex::new(configuration))), descriptors)
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn play_as_starts_the_game_with_an_exchange_code() {
+    //writes the command line it was given
+    let folder = test_folder("game", "play");
+    let output = folder.join("arguments.txt");
+    let executable = fake_game(&folder, &format!("printf '%s\\n' \"$@\" > \"{}.tmp\"\nmv \"{0}.tmp\" \"{0}\"", output.display()));
+
+    let (account_service, descriptors) = game_service(&executable, &["PlayAsUser"]).await;
+    let account_id = descriptors[0].device_auth.as_ref().unwrap().account_id.clone();
+    account_service.set_launch_args(&account_id, " -nosound ").await.unwrap();
+    let live_sessions = environment().server.live_sessions(&account_id);
+
+    let outcome = account_service.play_as(&account_id).await.unwrap();
+    assert!(matches!(outcome, AccountOutcome::GameStarted(account, _) if account.launch_args == "-nosound"));
+    //the game redeems the exchange code on its own
+    assert_eq!(environment().server.live_sessions(&account_id), live_sessions);
+
+    let mut arguments = None;
+    for _ in 0..50 {
+        if let Ok(data) = std::fs::read_to_string(&output) {
+            arguments = Some(data);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
+    let arguments: Vec<String> = arguments.expect("The game was not started").lines().map(String::from).collect();
+    assert!(arguments.contains(&"-AUTH_TYPE=exchangecode".to_string()));
+    assert!(arguments.contains(&format!("-epicuserid={}", account_id)));
+    assert!(arguments.iter().any(|x| x.starts_with("-AUTH_PASSWORD=") && x.len() > "-AUTH_PASSWORD=".len()));
+    assert_eq!(arguments.last().map(|x| x.as_str()), Some("-nosound"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn game_instances_are_tracked_per_account() {
+    //the game is a child of the process we start, like FortniteLauncher.exe and the client
+    let folder = test_folder("game", "instances");
+    let executable = fake_game(&folder, "sleep 30 &\nwait");
+
+    let (account_service, descriptors) = game_service(&executable, &["InstanceUserA", "InstanceUserB"]).await;
+    let user_a = descriptors[0].account_id().unwrap().to_string();
+    let user_b = descriptors[1].account_id().unwrap().to_string();
+    let (event_sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let account_service = account_service.with_events(event_sender);
+
+    account_service.play_as(&user_a).await.unwrap();
+    account_service.play_as(&user_b).await.unwrap();
+    assert!(account_service.play_as(&user_a).await.is_err());
+
+    let outcome = account_service.stop_game(&user_a).await.unwrap();
+    assert!(matches!(outcome, AccountOutcome::GameStopped(account) if account.display_name == "InstanceUserA"));
+    assert!(account_service.stop_game(&user_a).await.is_err());
+
+    account_service.stop_game(&user_b).await.unwrap();
+
+    let mut changes = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if let AccountEvent::GameInstancesChanged(in_game) = event {
+            changes.push(in_game);
+        }
+    }
+
+    assert_eq!(
+        changes,
+        vec![
+            vec![user_a.clone()],
+            vec![user_a.clone(), user_b.clone()],
+            vec![user_b.clone()],
+            vec![],
+        ]
+    );
+}
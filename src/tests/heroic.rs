@@ -0,0 +1,68 @@
+use std::{path::PathBuf, sync::Arc};
+
+use tokio::sync::Mutex;
+
+use crate::{
+    accounts::{AccountOutcome, AccountService},
+    heroic::Heroic,
+    launchers::Launchers,
+    legendary::{Legendary, LegendaryUserData},
+};
+
+use super::{
+    copy_legendary_fixture, environment, launcher_client, launcher_configuration, test_folder, workflows::add_mock_account,
+};
+
+//heroic keeps the legendary user.json two folders deep
+fn heroic_folder(name: &str) -> PathBuf {
+    let folder = test_folder("heroic", name).join("heroic");
+    copy_legendary_fixture(&folder.join("legendaryConfig").join("legendary"));
+    folder
+}
+
+#[test]
+fn reads_the_heroic_session() {
+    let folder = heroic_folder("read");
+    let launcher = Launchers::Heroic.resolve(&launcher_configuration(Launchers::Heroic, &folder)).unwrap();
+
+    assert_eq!(launcher.kind(), Launchers::Heroic);
+    assert_eq!(launcher.config_path().unwrap(), folder);
+    assert_eq!(launcher.read_active_account().unwrap().display_name, "LegendaryFixture");
+}
+
+#[test]
+fn flatpak_installs_are_detected() {
+    let native = Heroic::new(PathBuf::from("/home/user/.config/heroic"));
+    let flatpak = Heroic::new(PathBuf::from(
+        "/home/user/.var/app/com.heroicgameslauncher.hgl/config/heroic",
+    ));
+
+    assert_ne!(native, flatpak);
+    assert_eq!(
+        flatpak,
+        Heroic::from_configuration_path("/home/user/.var/app/com.heroicgameslauncher.hgl/config/heroic").unwrap()
+    );
+}
+
+#[tokio::test]
+async fn swap_writes_the_heroic_session() {
+    let folder = heroic_folder("swap");
+    let mut configuration = launcher_configuration(Launchers::Heroic, &folder);
+    let descriptor = add_mock_account(&mut configuration, "HeroicSwapUser").await;
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    let outcome = account_service.swap_account(descriptor.account_id().unwrap()).await.unwrap();
+    assert!(matches!(outcome, AccountOutcome::Swapped(_, Launchers::Heroic)));
+
+    let data: LegendaryUserData = Legendary::new(folder.join("legendaryConfig").join("legendary"))
+        .get_user_data()
+        .unwrap();
+    assert_eq!(data.display_name, "HeroicSwapUser");
+    assert_eq!(data.client_id, launcher_client().id);
+    assert!(environment()
+        .server
+        .is_refresh_token_valid(&data.refresh_token, &data.account_id));
+
+    let current = account_service.current_account().await.unwrap();
+    assert_eq!(current.display_name, "HeroicSwapUser");
+}
@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+use reqwest::StatusCode;
+
+use crate::{
+    epic::{error_codes, EpicErrorKind},
+    http::{self, HttpSettings},
+};
+
+use super::{environment, login_mock_account as login};
+
+#[test]
+fn backoff_doubles_up_to_the_longest_wait() {
+    let settings = HttpSettings {
+        backoff_milliseconds: 500,
+        max_retry_after_seconds: 3,
+        ..HttpSettings::default()
+    };
+
+    assert_eq!(settings.backoff(0), Duration::from_millis(500));
+    assert_eq!(settings.backoff(2), Duration::from_secs(2));
+    assert_eq!(settings.backoff(3), Duration::from_secs(3));
+    assert_eq!(settings.backoff(40), Duration::from_secs(3));
+}
+
+#[test]
+fn retry_after_is_read_in_seconds() {
+    assert_eq!(http::parse_retry_after(" 12 "), Some(Duration::from_secs(12)));
+    assert_eq!(http::parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+}
+
+#[tokio::test]
+async fn server_errors_are_retried() {
+    let account = login("FlakyServerUser").await;
+    let account_id = account.account_id.clone().unwrap();
+    environment()
+        .server
+        .fail_next_requests(&account_id, 2, StatusCode::BAD_GATEWAY);
+
+    let details = account.get_infos().await.expect("Failed to get account infos");
+
+    assert_eq!(details.display_name, "FlakyServerUser");
+    assert_eq!(environment().server.pending_faults(&account_id), 0);
+}
+
+#[tokio::test]
+async fn retries_give_up_with_the_last_error() {
+    let account = login("DownServerUser").await;
+    let account_id = account.account_id.clone().unwrap();
+    environment()
+        .server
+        .fail_next_requests(&account_id, 10, StatusCode::INTERNAL_SERVER_ERROR);
+
+    let error = account.get_infos().await.unwrap_err();
+
+    let EpicErrorKind::Api(api_error) = error.kind() else {
+        panic!("{:?} is not an api error", error);
+    };
+    assert_eq!(api_error.status, 500);
+    assert_eq!(
+        environment().server.pending_faults(&account_id),
+        10 - (HttpSettings::default().max_retries as usize + 1)
+    );
+}
+
+#[tokio::test]
+async fn posts_are_not_retried_after_a_server_error() {
+    let account = login("SingleDeviceAuthUser").await;
+    let account_id = account.account_id.clone().unwrap();
+    environment()
+        .server
+        .fail_next_requests(&account_id, 1, StatusCode::INTERNAL_SERVER_ERROR);
+
+    assert!(account.create_device_auth().await.is_err());
+    assert!(environment().server.account(&account_id).unwrap().device_auths.is_empty());
+
+    //503 means the request was not handled
+    environment()
+        .server
+        .fail_next_requests(&account_id, 1, StatusCode::SERVICE_UNAVAILABLE);
+
+    account.create_device_auth().await.expect("Failed to create device auth");
+    assert_eq!(environment().server.account(&account_id).unwrap().device_auths.len(), 1);
+}
+
+#[tokio::test]
+async fn throttled_requests_wait_for_retry_after() {
+    let account = login("RetryAfterUser").await;
+    let account_id = account.account_id.clone().unwrap();
+    environment().server.throttle_next_requests(&account_id, 1, 1, true);
+
+    let started = Instant::now();
+    account.get_infos().await.expect("Failed to get account infos");
+
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn throttled_requests_wait_for_the_epic_message() {
+    let account = login("ThrottledUser").await;
+    let account_id = account.account_id.clone().unwrap();
+    environment().server.throttle_next_requests(&account_id, 1, 1, false);
+
+    let started = Instant::now();
+    account.get_infos().await.expect("Failed to get account infos");
+
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn long_throttles_fail_right_away() {
+    let account = login("LongThrottleUser").await;
+    let account_id = account.account_id.clone().unwrap();
+    let seconds = HttpSettings::default().max_retry_after_seconds + 1;
+    environment().server.throttle_next_requests(&account_id, 1, seconds, true);
+
+    let started = Instant::now();
+    let error = account.get_infos().await.unwrap_err();
+
+    assert_eq!(error.error_code(), Some(error_codes::THROTTLED));
+    assert!(started.elapsed() < Duration::from_secs(seconds));
+}
@@ -0,0 +1,136 @@
+use std::{path::PathBuf, sync::Arc};
+
+use tokio::sync::Mutex;
+
+use crate::{
+    accounts::{AccountEvent, AccountOutcome, AccountProgress, AccountService},
+    egl::RememberMeEntry,
+    launchers::Launchers,
+    legendary::{Legendary, LegendaryUserData},
+};
+
+use super::{
+    copy_legendary_fixture, environment, launcher_client, launcher_configuration, mock_account, test_folder,
+    workflows::add_mock_account,
+};
+
+fn legendary_folder(name: &str) -> PathBuf {
+    let folder = test_folder("legendary", name);
+    copy_legendary_fixture(&folder);
+    folder
+}
+
+#[test]
+fn reads_the_fixture_session() {
+    let legendary = Legendary::new(legendary_folder("read"));
+
+    let entry = legendary.get_remember_me_data().unwrap();
+    assert_eq!(entry.display_name, "LegendaryFixture");
+    assert_eq!(entry.token, "fixture-refresh-token");
+
+    let data = legendary.get_user_data().unwrap();
+    assert_eq!(data.expires_at.as_deref(), Some("2023-11-20T18:00:00.000Z"));
+    assert_eq!(data.extra.get("product_id").and_then(|x| x.as_str()), Some("prod-fn"));
+}
+
+#[test]
+fn unknown_fields_survive_a_rewrite() {
+    let legendary = Legendary::new(legendary_folder("rewrite"));
+
+    let data = legendary.get_user_data().unwrap();
+    legendary.set_user_data(&data).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(legendary.user_path()).unwrap()).unwrap();
+    assert_eq!(json["device_id"], "fixturedevice");
+    assert_eq!(json["displayName"], "LegendaryFixture");
+}
+
+#[tokio::test]
+async fn swap_writes_the_legendary_session() {
+    let folder = legendary_folder("swap");
+    let mut configuration = launcher_configuration(Launchers::Legendary, &folder);
+    let descriptor = add_mock_account(&mut configuration, "LegendarySwapUser").await;
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    let outcome = account_service.swap_account(descriptor.account_id().unwrap()).await.unwrap();
+    assert!(matches!(outcome, AccountOutcome::Swapped(_, Launchers::Legendary)));
+
+    let data: LegendaryUserData = Legendary::new(folder).get_user_data().unwrap();
+    assert_eq!(data.display_name, "LegendarySwapUser");
+    assert_eq!(data.client_id, launcher_client().id);
+    assert!(data.expires_at.is_some());
+    assert!(environment()
+        .server
+        .is_refresh_token_valid(&data.refresh_token, &data.account_id));
+}
+
+#[tokio::test]
+async fn link_reads_the_legendary_session() {
+    let folder = legendary_folder("link");
+    let account = mock_account("LegendaryLinkUser");
+    let refresh_token = environment()
+        .server
+        .create_refresh_token(&account.account_id, launcher_client().id);
+
+    let legendary = Legendary::new(folder.clone());
+    let mut data = legendary.get_user_data().unwrap();
+    data.display_name = account.display_name.clone();
+    data.account_id = account.account_id.clone();
+    data.refresh_token = refresh_token;
+    legendary.set_user_data(&data).unwrap();
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(launcher_configuration(Launchers::Legendary, &folder))));
+
+    let current: RememberMeEntry = account_service.current_account().await.unwrap();
+    assert_eq!(current.display_name, "LegendaryLinkUser");
+
+    let outcome = account_service.link_current_account().await.unwrap();
+    assert!(matches!(outcome, AccountOutcome::Linked(account, Launchers::Legendary) if account.display_name == "LegendaryLinkUser"));
+}
+
+#[test]
+fn configuration_resolves_to_the_legendary_launcher() {
+    let folder = legendary_folder("resolve");
+    let launcher = Launchers::Legendary
+        .resolve(&launcher_configuration(Launchers::Legendary, &folder))
+        .unwrap();
+
+    assert_eq!(launcher.kind(), Launchers::Legendary);
+    assert_eq!(launcher.config_path().unwrap(), folder);
+    assert_eq!(launcher.read_active_account().unwrap().display_name, "LegendaryFixture");
+
+    //nothing to relaunch, the next legendary command picks the session up
+    assert!(launcher.start(None).is_ok());
+}
+
+#[tokio::test]
+async fn swap_reports_every_phase() {
+    let folder = legendary_folder("phases");
+    let mut configuration = launcher_configuration(Launchers::Legendary, &folder);
+    configuration.restart_launcher_on_swap = true;
+    let descriptor = add_mock_account(&mut configuration, "LegendaryPhasesUser").await;
+
+    let (event_sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration))).with_events(event_sender);
+    account_service.swap_account(descriptor.account_id().unwrap()).await.unwrap();
+    drop(account_service);
+
+    let mut phases = Vec::new();
+    while let Some(event) = events.recv().await {
+        if let AccountEvent::Progress(progress) = event {
+            phases.push(progress);
+        }
+    }
+
+    //legendary is not running, there is nothing to close
+    assert_eq!(
+        phases,
+        vec![
+            AccountProgress::LoggingIn("LegendaryPhasesUser".to_string()),
+            AccountProgress::WritingLauncherSession,
+            AccountProgress::VerifyingLauncherSession,
+            AccountProgress::StartingLauncher(Launchers::Legendary),
+        ]
+    );
+}
@@ -0,0 +1,147 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::Mutex;
+
+use crate::{
+    accounts::{AccountOutcome, AccountService},
+    config::Configuration,
+    loopback::{self, LoopbackListener},
+};
+
+use super::{environment, launcher_client, mock_account};
+
+async fn get(url: &str) -> String {
+    reqwest::get(url).await.unwrap().text().await.unwrap()
+}
+
+#[test]
+fn codes_are_found_in_what_the_user_pastes() {
+    let code = "0123456789abcdef0123456789abcdef";
+
+    assert_eq!(
+        loopback::extract_code(&format!(
+            r#"{{"redirectUrl":"https://localhost/launcher/authorized?code={0}","authorizationCode":"{0}","exchangeCode":null,"sid":null}}"#,
+            code
+        )),
+        Some(code.to_string())
+    );
+    assert_eq!(
+        loopback::extract_code(&format!(r#"{{"redirectUrl":"https://localhost/?code={}","authorizationCode":null}}"#, code)),
+        Some(code.to_string())
+    );
+    assert_eq!(
+        loopback::extract_code(&format!("https://localhost/launcher/authorized?code={}", code)),
+        Some(code.to_string())
+    );
+    assert_eq!(loopback::extract_code(&format!(" \"{}\"\n", code)), Some(code.to_string()));
+    assert_eq!(loopback::extract_code(r#"{"authorizationCode":null}"#), None);
+    assert_eq!(loopback::extract_code("not a code"), None);
+}
+
+#[test]
+fn login_urls_name_the_client() {
+    let login_url = loopback::login_url(&launcher_client());
+
+    assert!(login_url.starts_with("https://www.epicgames.com/id/login?redirectUrl="));
+    assert!(login_url.contains(launcher_client().id));
+
+    let authorize_url = loopback::authorize_url(&launcher_client(), "http://127.0.0.1:1234/", "state");
+    assert!(authorize_url.contains(&format!("client_id={}", launcher_client().id)));
+    assert!(authorize_url.contains("redirect_uri=http%3A%2F%2F127.0.0.1%3A1234%2F"));
+}
+
+#[tokio::test]
+async fn the_local_page_guides_the_login() {
+    let listener = LoopbackListener::bind(&launcher_client()).unwrap();
+    let url = listener.local_url();
+
+    let waiting = tokio::spawn(async move { listener.wait_for_code().await });
+    let page = get(&url).await;
+
+    assert!(page.contains(launcher_client().id));
+    assert!(!waiting.is_finished());
+    waiting.abort();
+}
+
+#[tokio::test]
+async fn redirected_codes_are_captured() {
+    let listener = LoopbackListener::bind(&launcher_client()).unwrap();
+    let (url, state) = (listener.local_url(), listener.state().to_string());
+
+    let waiting = tokio::spawn(async move { listener.wait_for_code().await });
+    //other pages don't know the state
+    get(&format!("{}?code=forged&state=other", url)).await;
+    get(&format!("{}?code=redirected&state={}", url, state)).await;
+
+    assert_eq!(waiting.await.unwrap().unwrap(), "redirected");
+}
+
+#[tokio::test]
+async fn idle_connections_do_not_block_the_code() {
+    let listener = LoopbackListener::bind(&launcher_client()).unwrap();
+    let (url, state) = (listener.local_url(), listener.state().to_string());
+
+    let waiting = tokio::spawn(async move { listener.wait_for_code().await });
+    //like the speculative connection of a browser, it never sends a request
+    let _idle = tokio::net::TcpStream::connect(url.trim_start_matches("http://").trim_end_matches('/'))
+        .await
+        .unwrap();
+
+    let code = tokio::time::timeout(Duration::from_secs(5), async {
+        get(&format!("{}?code=after-idle&state={}", url, state)).await;
+        waiting.await.unwrap().unwrap()
+    })
+    .await
+    .expect("The idle connection blocked the listener");
+
+    assert_eq!(code, "after-idle");
+}
+
+#[tokio::test]
+async fn pasted_codes_are_captured() {
+    let listener = LoopbackListener::bind(&launcher_client()).unwrap();
+    let (url, state) = (listener.local_url(), listener.state().to_string());
+
+    let waiting = tokio::spawn(async move { listener.wait_for_code().await });
+    let value = r#"{"redirectUrl":"https://localhost/launcher/authorized?code=pasted","authorizationCode":"pasted"}"#;
+    let page = reqwest::Client::new()
+        .get(&url)
+        .query(&[("state", state.as_str()), ("value", value)])
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+
+    assert!(page.contains("you can close this tab"));
+    assert_eq!(waiting.await.unwrap().unwrap(), "pasted");
+}
+
+#[tokio::test]
+async fn refused_logins_are_reported() {
+    let listener = LoopbackListener::bind(&launcher_client()).unwrap();
+    let (url, state) = (listener.local_url(), listener.state().to_string());
+
+    let waiting = tokio::spawn(async move { listener.wait_for_code().await });
+    get(&format!("{}?error=access_denied&error_description=Denied&state={}", url, state)).await;
+
+    assert!(waiting.await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn accounts_are_added_from_the_browser() {
+    let account = mock_account("BrowserLoginUser");
+    let code = environment().server.create_authorization_code(&account.account_id);
+
+    let listener = LoopbackListener::bind(&launcher_client()).unwrap();
+    let url = format!("{}?code={}&state={}", listener.local_url(), code, listener.state());
+    let account_service = AccountService::new(Arc::new(Mutex::new(Configuration::default())));
+
+    let (outcome, _) = tokio::join!(account_service.add_with_browser(&listener, launcher_client()), get(&url));
+
+    let AccountOutcome::Added(descriptor) = outcome.unwrap() else {
+        panic!("the account was not added");
+    };
+    assert_eq!(descriptor.display_name, "BrowserLoginUser");
+}
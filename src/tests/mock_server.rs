@@ -0,0 +1,958 @@
+//in-process stand-in for the Epic account, eulatracking and cloudstorage services
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use base64::{engine::general_purpose, Engine};
+use hyper::{
+    body::to_bytes,
+    header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde_json::json;
+
+#[derive(Debug, Clone)]
+pub struct MockDeviceAuth {
+    pub device_id: String,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MockAccount {
+    pub account_id: String,
+    pub display_name: String,
+    pub email: String,
+    pub device_auths: Vec<MockDeviceAuth>,
+    pub files: HashMap<String, Vec<u8>>,
+    pub eula_accepted: bool,
+    pub fortnite_access: bool,
+    pub banned: bool,
+    //correctiveAction returned by the next logins
+    pub corrective_action: Option<String>,
+    //expires_in of the next sessions, in seconds
+    pub session_lifetime: i64,
+}
+
+#[derive(Debug, Clone)]
+struct MockSession {
+    account_id: Option<String>,
+    client_id: String,
+    refresh_token: Option<String>,
+}
+
+//answers the next requests about an account instead of the real route
+#[derive(Debug, Clone)]
+struct MockFault {
+    account_id: String,
+    status: StatusCode,
+    //seconds asked by errors.com.epicgames.common.throttled
+    throttled_for: Option<u64>,
+    retry_after_header: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct MockState {
+    pub accounts: HashMap<String, MockAccount>,
+    sessions: HashMap<String, MockSession>,
+    refresh_tokens: HashMap<String, MockSession>,
+    exchange_codes: HashMap<String, String>,
+    authorization_codes: HashMap<String, String>,
+    //device_code -> account approved by the user, if any
+    device_codes: HashMap<String, Option<String>>,
+    //continuation -> account waiting for a corrective action
+    continuations: HashMap<String, String>,
+    faults: Vec<MockFault>,
+    counter: u64,
+}
+
+impl MockState {
+    fn next_id(&mut self) -> String {
+        self.counter += 1;
+        format!("{:032x}", self.counter)
+    }
+
+    fn create_session(&mut self, account_id: Option<String>, client_id: &str) -> serde_json::Value {
+        let access_token = self.next_id();
+        let mut session = MockSession {
+            account_id: account_id.clone(),
+            client_id: client_id.to_string(),
+            refresh_token: None,
+        };
+
+        let account_id = match account_id {
+            Some(account_id) => account_id,
+            None => {
+                self.sessions.insert(access_token.clone(), session);
+
+                return json!({
+                    "access_token": access_token,
+                    "expires_in": 14400,
+                    "token_type": "bearer",
+                    "client_id": client_id,
+                    "internal_client": true,
+                    "client_service": "fortnite",
+                });
+            }
+        };
+
+        let refresh_token = self.next_id();
+        session.refresh_token = Some(refresh_token.clone());
+        self.sessions.insert(access_token.clone(), session.clone());
+        self.refresh_tokens.insert(refresh_token.clone(), session);
+
+        let (display_name, session_lifetime) = self
+            .accounts
+            .get(&account_id)
+            .map(|x| (x.display_name.clone(), x.session_lifetime))
+            .unwrap_or_default();
+
+        json!({
+            "access_token": access_token,
+            "expires_in": session_lifetime,
+            "token_type": "bearer",
+            "refresh_token": refresh_token,
+            "refresh_expires": 28800,
+            "expires_at": "2099-01-01T02:00:00.000Z",
+            "refresh_expires_at": "2099-01-01T08:00:00.000Z",
+            "account_id": account_id,
+            "client_id": client_id,
+            "internal_client": true,
+            "client_service": "fortnite",
+            "displayName": display_name,
+            "app": "fortnite",
+            "in_app_id": account_id,
+        })
+    }
+
+    fn access_token<'a>(&self, request: &'a Request<Body>) -> Option<&'a str> {
+        let header = request.headers().get(AUTHORIZATION)?.to_str().ok()?;
+        header.strip_prefix("bearer ").or(header.strip_prefix("Bearer "))
+    }
+
+    fn session(&self, request: &Request<Body>) -> Option<MockSession> {
+        self.sessions.get(self.access_token(request)?).cloned()
+    }
+
+    //the refresh token of a session dies with it
+    fn kill_session(&mut self, access_token: &str) {
+        if let Some(refresh_token) = self.sessions.remove(access_token).and_then(|x| x.refresh_token) {
+            self.refresh_tokens.remove(&refresh_token);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MockEpicServer {
+    pub base_url: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockEpicServer {
+    //the server runs on its own runtime so it outlives the runtime of any single test
+    pub fn start() -> Self {
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let server_state = Arc::clone(&state);
+        let (address_sender, address_receiver) = std::sync::mpsc::channel::<SocketAddr>();
+
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().expect("Unable to create mock Runtime");
+
+            runtime.block_on(async move {
+                let make_service = make_service_fn(move |_| {
+                    let state = Arc::clone(&server_state);
+                    async move {
+                        Ok::<_, Infallible>(service_fn(move |request| {
+                            handle(Arc::clone(&state), request)
+                        }))
+                    }
+                });
+
+                let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+                let _ = address_sender.send(server.local_addr());
+                let _ = server.await;
+            });
+        });
+
+        let address = address_receiver.recv().expect("Mock server failed to start");
+
+        Self {
+            base_url: format!("http://{}", address),
+            state,
+        }
+    }
+
+    pub fn add_account(&self, display_name: impl Into<String>) -> MockAccount {
+        let mut state = self.state.lock().unwrap();
+        let account_id = state.next_id();
+        let display_name = display_name.into();
+
+        let account = MockAccount {
+            account_id: account_id.clone(),
+            email: format!("{}@alt-manager.test", display_name.to_lowercase()),
+            display_name,
+            device_auths: Vec::new(),
+            files: HashMap::new(),
+            eula_accepted: false,
+            fortnite_access: false,
+            banned: false,
+            corrective_action: None,
+            session_lifetime: 7200,
+        };
+
+        state.accounts.insert(account_id, account.clone());
+        account
+    }
+
+    pub fn account(&self, account_id: &str) -> Option<MockAccount> {
+        self.state.lock().unwrap().accounts.get(account_id).cloned()
+    }
+
+    //refresh token of a launcher session, like the one EGL keeps in GameUserSettings.ini
+    pub fn create_refresh_token(&self, account_id: &str, client_id: &str) -> String {
+        let mut state = self.state.lock().unwrap();
+        let session = state.create_session(Some(account_id.to_string()), client_id);
+        session["refresh_token"].as_str().unwrap().to_string()
+    }
+
+    pub fn create_authorization_code(&self, account_id: &str) -> String {
+        let mut state = self.state.lock().unwrap();
+        let code = state.next_id();
+        state.authorization_codes.insert(code.clone(), account_id.to_string());
+        code
+    }
+
+    pub fn is_refresh_token_valid(&self, refresh_token: &str, account_id: &str) -> bool {
+        let state = self.state.lock().unwrap();
+        state
+            .refresh_tokens
+            .get(refresh_token)
+            .is_some_and(|x| x.account_id.as_deref() == Some(account_id))
+    }
+
+    //simulates the user entering the code on epicgames.com/activate
+    pub fn approve_device_code(&self, device_code: &str, account_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .device_codes
+            .insert(device_code.to_string(), Some(account_id.to_string()));
+    }
+
+    //simulates a rename on epicgames.com, the device auths stay valid
+    pub fn rename_account(&self, account_id: &str, display_name: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(account) = state.accounts.get_mut(account_id) {
+            account.display_name = display_name.to_string();
+        }
+    }
+
+    //what a password reset does to the device auths
+    pub fn revoke_device_auths(&self, account_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(account) = state.accounts.get_mut(account_id) {
+            account.device_auths.clear();
+        }
+    }
+
+    pub fn set_session_lifetime(&self, account_id: &str, seconds: i64) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(account) = state.accounts.get_mut(account_id) {
+            account.session_lifetime = seconds;
+        }
+    }
+
+    pub fn expire_access_tokens(&self, account_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.sessions.retain(|_, x| x.account_id.as_deref() != Some(account_id));
+    }
+
+    //the device auths stay valid
+    pub fn revoke_refresh_tokens(&self, account_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.refresh_tokens.retain(|_, x| x.account_id.as_deref() != Some(account_id));
+    }
+
+    pub fn live_sessions(&self, account_id: &str) -> usize {
+        let state = self.state.lock().unwrap();
+        state
+            .sessions
+            .values()
+            .filter(|x| x.account_id.as_deref() == Some(account_id))
+            .count()
+    }
+
+    pub fn require_corrective_action(&self, account_id: &str, corrective_action: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(account) = state.accounts.get_mut(account_id) {
+            account.corrective_action = Some(corrective_action.to_string());
+        }
+    }
+
+    pub fn ban_account(&self, account_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(account) = state.accounts.get_mut(account_id) {
+            account.banned = true;
+        }
+    }
+
+    pub fn fail_next_requests(&self, account_id: &str, count: usize, status: StatusCode) {
+        let fault = MockFault {
+            account_id: account_id.to_string(),
+            status,
+            throttled_for: None,
+            retry_after_header: false,
+        };
+
+        self.state.lock().unwrap().faults.extend(vec![fault; count]);
+    }
+
+    pub fn throttle_next_requests(&self, account_id: &str, count: usize, seconds: u64, retry_after_header: bool) {
+        let fault = MockFault {
+            account_id: account_id.to_string(),
+            status: StatusCode::TOO_MANY_REQUESTS,
+            throttled_for: Some(seconds),
+            retry_after_header,
+        };
+
+        self.state.lock().unwrap().faults.extend(vec![fault; count]);
+    }
+
+    pub fn pending_faults(&self, account_id: &str) -> usize {
+        let state = self.state.lock().unwrap();
+        state.faults.iter().filter(|x| x.account_id == account_id).count()
+    }
+
+    pub fn set_file(&self, account_id: &str, unique_file_name: &str, data: Vec<u8>) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(account) = state.accounts.get_mut(account_id) {
+            account.files.insert(unique_file_name.to_string(), data);
+        }
+    }
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn empty_response(status: StatusCode) -> Response<Body> {
+    Response::builder().status(status).body(Body::empty()).unwrap()
+}
+
+fn error_response(status: StatusCode, error_code: &str, message: &str, numeric_error_code: i64) -> Response<Body> {
+    json_response(
+        status,
+        json!({
+            "errorCode": error_code,
+            "errorMessage": message,
+            "messageVars": [],
+            "numericErrorCode": numeric_error_code,
+            "originatingService": "com.epicgames.account.public",
+            "intent": "prod",
+        }),
+    )
+}
+
+fn unauthorized() -> Response<Body> {
+    error_response(
+        StatusCode::UNAUTHORIZED,
+        "errors.com.epicgames.common.authentication.authentication_failed",
+        "Authentication failed",
+        1032,
+    )
+}
+
+fn invalid_grant(message: &str) -> Response<Body> {
+    error_response(
+        StatusCode::BAD_REQUEST,
+        "errors.com.epicgames.account.oauth.invalid_grant",
+        message,
+        18031,
+    )
+}
+
+fn fault_response(fault: &MockFault) -> Response<Body> {
+    let Some(seconds) = fault.throttled_for else {
+        return error_response(
+            fault.status,
+            "errors.com.epicgames.common.server_error",
+            "Sorry an error occurred and we were unable to resolve it",
+            1000,
+        );
+    };
+
+    let mut response = json_response(
+        fault.status,
+        json!({
+            "errorCode": "errors.com.epicgames.common.throttled",
+            "errorMessage": format!("Operation access is limited by throttling policy, please try again in {} second(s).", seconds),
+            "messageVars": [seconds.to_string()],
+            "numericErrorCode": 1041,
+            "originatingService": "com.epicgames.account.public",
+            "intent": "prod",
+        }),
+    );
+
+    if fault.retry_after_header {
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, seconds.to_string().parse().unwrap());
+    }
+
+    response
+}
+
+fn basic_auth_client_id(request: &Request<Body>) -> Option<String> {
+    let header = request.headers().get(AUTHORIZATION)?.to_str().ok()?;
+    let encoded = header.strip_prefix("Basic ").or(header.strip_prefix("basic "))?;
+    let decoded = String::from_utf8(general_purpose::STANDARD.decode(encoded).ok()?).ok()?;
+    decoded.split(':').next().map(|x| x.to_string())
+}
+
+async fn handle(
+    state: Arc<Mutex<MockState>>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let segments: Vec<String> = path
+        .trim_matches('/')
+        .split('/')
+        .map(|x| x.to_string())
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(|x| x.as_str()).collect();
+
+    let fault = {
+        let mut state = state.lock().unwrap();
+        let position = state
+            .faults
+            .iter()
+            .position(|x| segments.contains(&x.account_id.as_str()));
+        position.map(|x| state.faults.remove(x))
+    };
+
+    if let Some(fault) = fault {
+        return Ok(fault_response(&fault));
+    }
+
+    let response = match (method, segments.as_slice()) {
+        (Method::POST, ["account", "api", "oauth", "token"]) => token(state, request).await,
+        (Method::GET, ["account", "api", "oauth", "exchange"]) => exchange(state, request),
+        (Method::DELETE, ["account", "api", "oauth", "sessions", "kill", access_token]) => {
+            kill_session(state, request, access_token)
+        }
+        (Method::DELETE, ["account", "api", "oauth", "sessions", "kill"]) => kill_sessions(state, request),
+        (Method::POST, ["account", "api", "oauth", "deviceAuthorization"]) => {
+            device_authorization(state, request)
+        }
+        (Method::PUT, ["account", "api", "public", "corrections", "acceptPrivacyPolicy"]) => {
+            accept_privacy_policy(state, request).await
+        }
+        (Method::GET, ["account", "api", "public", "account", account_id]) => {
+            account_infos(state, request, account_id)
+        }
+        (Method::POST, ["account", "api", "public", "account", account_id, "deviceAuth"]) => {
+            create_device_auth(state, request, account_id)
+        }
+        (Method::GET, ["account", "api", "public", "account", account_id, "deviceAuth"]) => {
+            list_device_auths(state, request, account_id)
+        }
+        (Method::DELETE, ["account", "api", "public", "account", account_id, "deviceAuth", device_id]) => {
+            delete_device_auth(state, request, account_id, device_id)
+        }
+        (Method::GET, ["eulatracking", "api", "public", "agreements", "fn", "account", account_id]) => {
+            eula_data(state, request, account_id)
+        }
+        (
+            Method::POST,
+            ["eulatracking", "api", "public", "agreements", "fn", "version", _, "account", account_id, "accept"],
+        ) => accept_eula(state, request, account_id),
+        (Method::POST, ["fortnite", "api", "game", "v2", "grant_access", account_id]) => {
+            grant_access(state, request, account_id)
+        }
+        (Method::GET, ["fortnite", "api", "cloudstorage", "user", account_id]) => {
+            user_files(state, request, account_id)
+        }
+        (Method::GET, ["fortnite", "api", "cloudstorage", "user", account_id, file_name]) => {
+            user_file(state, request, account_id, file_name)
+        }
+        (Method::PUT, ["fortnite", "api", "cloudstorage", "user", account_id, file_name]) => {
+            let account_id = account_id.to_string();
+            let file_name = file_name.to_string();
+            put_user_file(state, request, &account_id, &file_name).await
+        }
+        _ => error_response(
+            StatusCode::NOT_FOUND,
+            "errors.com.epicgames.common.not_found",
+            "Sorry the resource you were trying to find could not be found",
+            1004,
+        ),
+    };
+
+    Ok(response)
+}
+
+async fn token(state: Arc<Mutex<MockState>>, request: Request<Body>) -> Response<Body> {
+    let client_id = match basic_auth_client_id(&request) {
+        Some(client_id) => client_id,
+        None => {
+            return error_response(
+                StatusCode::UNAUTHORIZED,
+                "errors.com.epicgames.account.invalid_client_credentials",
+                "Sorry the client credentials you are using are invalid",
+                18033,
+            )
+        }
+    };
+
+    let body = to_bytes(request.into_body()).await.unwrap_or_default();
+    let params: HashMap<String, String> = serde_urlencoded::from_bytes(&body).unwrap_or_default();
+    let param = |name: &str| params.get(name).cloned().unwrap_or_default();
+
+    let mut state = state.lock().unwrap();
+
+    let account_id = match param("grant_type").as_str() {
+        "client_credentials" => None,
+        "refresh_token" => match state.refresh_tokens.get(&param("refresh_token")) {
+            Some(session) => session.account_id.clone(),
+            None => return invalid_grant("Sorry the refresh token is invalid"),
+        },
+        "exchange_code" => match state.exchange_codes.remove(&param("exchange_code")) {
+            Some(account_id) => Some(account_id),
+            None => return invalid_grant("Sorry the exchange code you supplied was not found"),
+        },
+        "authorization_code" => match state.authorization_codes.remove(&param("code")) {
+            Some(account_id) => Some(account_id),
+            None => return invalid_grant("Sorry the authorization code you supplied was not found"),
+        },
+        "device_auth" => {
+            let account_id = param("account_id");
+            let is_valid = state.accounts.get(&account_id).is_some_and(|account| {
+                account
+                    .device_auths
+                    .iter()
+                    .any(|x| x.device_id == param("device_id") && x.secret == param("secret"))
+            });
+
+            if !is_valid {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    "errors.com.epicgames.account.invalid_account_credentials",
+                    "Sorry the account credentials you are using are invalid",
+                    18031,
+                );
+            }
+
+            if state.accounts.get(&account_id).is_some_and(|account| account.banned) {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    "errors.com.epicgames.account.account_not_active",
+                    "Sorry the account you are using is not active",
+                    18007,
+                );
+            }
+
+            let corrective_action = state.accounts.get(&account_id).and_then(|x| x.corrective_action.clone());
+            if let Some(corrective_action) = corrective_action {
+                let continuation = state.next_id();
+                state.continuations.insert(continuation.clone(), account_id);
+
+                return json_response(
+                    StatusCode::BAD_REQUEST,
+                    json!({
+                        "errorCode": "errors.com.epicgames.oauth.corrective_action_required",
+                        "errorMessage": "Corrective action is required to continue.",
+                        "messageVars": [],
+                        "numericErrorCode": 18206,
+                        "originatingService": "com.epicgames.account.public",
+                        "intent": "prod",
+                        "correctiveAction": corrective_action,
+                        "continuation": continuation,
+                    }),
+                );
+            }
+
+            Some(account_id)
+        }
+        "device_code" => match state.device_codes.get(&param("device_code")).cloned() {
+            Some(Some(account_id)) => {
+                state.device_codes.remove(&param("device_code"));
+                Some(account_id)
+            }
+            Some(None) => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    "errors.com.epicgames.account.oauth.authorization_pending",
+                    "The authorization server request is still pending",
+                    1012,
+                )
+            }
+            None => return invalid_grant("Sorry the device code you supplied was not found"),
+        },
+        _ => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "errors.com.epicgames.common.oauth.unsupported_grant_type",
+                "Unsupported grant type",
+                1016,
+            )
+        }
+    };
+
+    json_response(StatusCode::OK, state.create_session(account_id, &client_id))
+}
+
+async fn accept_privacy_policy(state: Arc<Mutex<MockState>>, request: Request<Body>) -> Response<Body> {
+    let body = to_bytes(request.into_body()).await.unwrap_or_default();
+    let continuation = serde_json::from_slice::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|x| x["continuation"].as_str().map(String::from))
+        .unwrap_or_default();
+
+    let mut state = state.lock().unwrap();
+    let Some(account_id) = state.continuations.remove(&continuation) else {
+        return invalid_grant("Sorry the continuation you supplied was not found");
+    };
+
+    match state.accounts.get_mut(&account_id) {
+        Some(account) if account.corrective_action.as_deref() == Some("PRIVACY_POLICY_ACCEPTANCE") => {
+            account.corrective_action = None;
+            empty_response(StatusCode::NO_CONTENT)
+        }
+        _ => error_response(
+            StatusCode::BAD_REQUEST,
+            "errors.com.epicgames.common.bad_request",
+            "No privacy policy to accept",
+            1001,
+        ),
+    }
+}
+
+fn kill_session(state: Arc<Mutex<MockState>>, request: Request<Body>, access_token: &str) -> Response<Body> {
+    let mut state = state.lock().unwrap();
+
+    if state.session(&request).is_none() {
+        return unauthorized();
+    }
+
+    state.kill_session(access_token);
+    empty_response(StatusCode::NO_CONTENT)
+}
+
+//only killType=OTHERS is supported
+fn kill_sessions(state: Arc<Mutex<MockState>>, request: Request<Body>) -> Response<Body> {
+    let mut state = state.lock().unwrap();
+
+    let (Some(session), Some(current)) = (state.session(&request), state.access_token(&request)) else {
+        return unauthorized();
+    };
+
+    if request.uri().query() != Some("killType=OTHERS") {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "errors.com.epicgames.common.bad_request",
+            "Unsupported killType",
+            1001,
+        );
+    }
+
+    let others: Vec<String> = state
+        .sessions
+        .iter()
+        .filter(|(token, x)| x.account_id == session.account_id && token.as_str() != current)
+        .map(|(token, _)| token.clone())
+        .collect();
+
+    others.iter().for_each(|token| state.kill_session(token));
+    empty_response(StatusCode::NO_CONTENT)
+}
+
+fn exchange(state: Arc<Mutex<MockState>>, request: Request<Body>) -> Response<Body> {
+    let mut state = state.lock().unwrap();
+
+    let account_id = match state.session(&request).and_then(|x| x.account_id) {
+        Some(account_id) => account_id,
+        None => return unauthorized(),
+    };
+
+    let code = state.next_id();
+    state.exchange_codes.insert(code.clone(), account_id);
+
+    json_response(
+        StatusCode::OK,
+        json!({
+            "expiresInSeconds": 300,
+            "code": code,
+            "creatingClientId": "34a02cf8f4414e29b15921876da36f9a",
+        }),
+    )
+}
+
+fn device_authorization(state: Arc<Mutex<MockState>>, request: Request<Body>) -> Response<Body> {
+    let mut state = state.lock().unwrap();
+
+    let session = match state.session(&request) {
+        Some(session) => session,
+        None => return unauthorized(),
+    };
+
+    let device_code = state.next_id();
+    let user_code = device_code[24..].to_uppercase();
+    state.device_codes.insert(device_code.clone(), None);
+
+    json_response(
+        StatusCode::OK,
+        json!({
+            "user_code": user_code,
+            "device_code": device_code,
+            "verification_uri": "https://www.epicgames.com/activate",
+            "verification_uri_complete": format!("https://www.epicgames.com/activate?userCode={}", user_code),
+            "prompt": "login",
+            "expires_in": 600,
+            "interval": 1,
+            "client_id": session.client_id,
+        }),
+    )
+}
+
+//every account scoped route requires a session owned by that account
+fn authorize_account(state: &MockState, request: &Request<Body>, account_id: &str) -> Result<(), Box<Response<Body>>> {
+    match state.session(request) {
+        Some(session) if session.account_id.as_deref() == Some(account_id) => Ok(()),
+        Some(_) => Err(Box::new(error_response(
+            StatusCode::FORBIDDEN,
+            "errors.com.epicgames.common.missing_permission",
+            "Sorry your login does not posses the permissions required",
+            1023,
+        ))),
+        None => Err(Box::new(unauthorized())),
+    }
+}
+
+fn account_infos(state: Arc<Mutex<MockState>>, request: Request<Body>, account_id: &str) -> Response<Body> {
+    let state = state.lock().unwrap();
+
+    if let Err(response) = authorize_account(&state, &request, account_id) {
+        return *response;
+    }
+
+    let account = &state.accounts[account_id];
+
+    json_response(
+        StatusCode::OK,
+        json!({
+            "id": account.account_id,
+            "displayName": account.display_name,
+            "name": "Mock",
+            "lastName": "Account",
+            "email": account.email,
+            "failedLoginAttempts": 0,
+            "tfaEnabled": false,
+        }),
+    )
+}
+
+fn create_device_auth(state: Arc<Mutex<MockState>>, request: Request<Body>, account_id: &str) -> Response<Body> {
+    let mut state = state.lock().unwrap();
+
+    if let Err(response) = authorize_account(&state, &request, account_id) {
+        return *response;
+    }
+
+    let device_auth = MockDeviceAuth {
+        device_id: state.next_id(),
+        secret: state.next_id(),
+    };
+
+    state
+        .accounts
+        .get_mut(account_id)
+        .unwrap()
+        .device_auths
+        .push(device_auth.clone());
+
+    json_response(
+        StatusCode::OK,
+        json!({
+            "deviceId": device_auth.device_id,
+            "accountId": account_id,
+            "secret": device_auth.secret,
+            "userAgent": "alt-manager-tests",
+            "created": {
+                "location": "Localhost",
+                "ipAddress": "127.0.0.1",
+                "dateTime": "2023-11-20T00:00:00.000Z",
+            },
+        }),
+    )
+}
+
+fn list_device_auths(state: Arc<Mutex<MockState>>, request: Request<Body>, account_id: &str) -> Response<Body> {
+    let state = state.lock().unwrap();
+
+    if let Err(response) = authorize_account(&state, &request, account_id) {
+        return *response;
+    }
+
+    let device_auths: Vec<serde_json::Value> = state.accounts[account_id]
+        .device_auths
+        .iter()
+        .map(|x| {
+            json!({
+                "deviceId": x.device_id,
+                "accountId": account_id,
+                "userAgent": "alt-manager-tests",
+                "created": {
+                    "location": "Localhost",
+                    "ipAddress": "127.0.0.1",
+                    "dateTime": "2023-11-20T00:00:00.000Z",
+                },
+            })
+        })
+        .collect();
+
+    json_response(StatusCode::OK, json!(device_auths))
+}
+
+fn delete_device_auth(
+    state: Arc<Mutex<MockState>>,
+    request: Request<Body>,
+    account_id: &str,
+    device_id: &str,
+) -> Response<Body> {
+    let mut state = state.lock().unwrap();
+
+    if let Err(response) = authorize_account(&state, &request, account_id) {
+        return *response;
+    }
+
+    let device_auths = &mut state.accounts.get_mut(account_id).unwrap().device_auths;
+
+    match device_auths.iter().position(|x| x.device_id == device_id) {
+        Some(position) => {
+            device_auths.remove(position);
+            empty_response(StatusCode::NO_CONTENT)
+        }
+        None => error_response(
+            StatusCode::NOT_FOUND,
+            "errors.com.epicgames.account.device_auth.not_found",
+            "Sorry the device auth you are looking for could not be found",
+            18007,
+        ),
+    }
+}
+
+fn eula_data(state: Arc<Mutex<MockState>>, request: Request<Body>, account_id: &str) -> Response<Body> {
+    let state = state.lock().unwrap();
+
+    if let Err(response) = authorize_account(&state, &request, account_id) {
+        return *response;
+    }
+
+    if state.accounts[account_id].eula_accepted {
+        return empty_response(StatusCode::NO_CONTENT);
+    }
+
+    json_response(StatusCode::OK, json!({ "version": 3, "locale": "en" }))
+}
+
+fn accept_eula(state: Arc<Mutex<MockState>>, request: Request<Body>, account_id: &str) -> Response<Body> {
+    let mut state = state.lock().unwrap();
+
+    if let Err(response) = authorize_account(&state, &request, account_id) {
+        return *response;
+    }
+
+    state.accounts.get_mut(account_id).unwrap().eula_accepted = true;
+    empty_response(StatusCode::NO_CONTENT)
+}
+
+fn grant_access(state: Arc<Mutex<MockState>>, request: Request<Body>, account_id: &str) -> Response<Body> {
+    let mut state = state.lock().unwrap();
+
+    if let Err(response) = authorize_account(&state, &request, account_id) {
+        return *response;
+    }
+
+    state.accounts.get_mut(account_id).unwrap().fortnite_access = true;
+    empty_response(StatusCode::NO_CONTENT)
+}
+
+fn user_files(state: Arc<Mutex<MockState>>, request: Request<Body>, account_id: &str) -> Response<Body> {
+    let state = state.lock().unwrap();
+
+    if let Err(response) = authorize_account(&state, &request, account_id) {
+        return *response;
+    }
+
+    let files: Vec<serde_json::Value> = state.accounts[account_id]
+        .files
+        .iter()
+        .map(|(name, data)| {
+            json!({
+                "uniqueFilename": name,
+                "filename": name,
+                "hash": "",
+                "hash256": "",
+                "length": data.len(),
+                "contentType": "application/octet-stream",
+                "uploaded": "2023-11-20T00:00:00.000Z",
+                "storageType": "S3",
+                "accountId": account_id,
+            })
+        })
+        .collect();
+
+    json_response(StatusCode::OK, serde_json::Value::Array(files))
+}
+
+fn user_file(state: Arc<Mutex<MockState>>, request: Request<Body>, account_id: &str, file_name: &str) -> Response<Body> {
+    let state = state.lock().unwrap();
+
+    if let Err(response) = authorize_account(&state, &request, account_id) {
+        return *response;
+    }
+
+    match state.accounts[account_id].files.get(file_name) {
+        Some(data) => Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .body(Body::from(data.clone()))
+            .unwrap(),
+        None => error_response(
+            StatusCode::NOT_FOUND,
+            "errors.com.epicgames.cloudstorage.file_not_found",
+            "Sorry, we couldn't find a file for that account",
+            12004,
+        ),
+    }
+}
+
+async fn put_user_file(
+    state: Arc<Mutex<MockState>>,
+    request: Request<Body>,
+    account_id: &str,
+    file_name: &str,
+) -> Response<Body> {
+    if let Err(response) = authorize_account(&state.lock().unwrap(), &request, account_id) {
+        return *response;
+    }
+
+    let data = to_bytes(request.into_body()).await.unwrap_or_default();
+
+    let mut state = state.lock().unwrap();
+    state
+        .accounts
+        .get_mut(account_id)
+        .unwrap()
+        .files
+        .insert(file_name.to_string(), data.to_vec());
+
+    empty_response(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,126 @@
+mod cli;
+mod device_auths;
+mod errors;
+mod game;
+mod heroic;
+mod http;
+mod legendary;
+mod loopback;
+mod mock_server;
+mod process;
+mod secret_store;
+mod sessions;
+mod workflows;
+
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use lazy_static::lazy_static;
+
+use crate::{
+    config::Configuration,
+    endpoints,
+    epic::{self, EpicAccount, Token},
+    epic_clients::AuthClient,
+    launchers::Launchers,
+    get_client,
+};
+
+use self::mock_server::{MockAccount, MockEpicServer};
+
+lazy_static! {
+    //GameUserSettings.ini is shared by every test swapping or reading the EGL account
+    pub static ref GAME_USER_SETTINGS_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+pub struct TestEnvironment {
+    pub server: MockEpicServer,
+}
+
+static ENVIRONMENT: OnceLock<TestEnvironment> = OnceLock::new();
+
+//starts the mock server once and points the endpoints, configuration and EGL paths at it
+pub fn environment() -> &'static TestEnvironment {
+    ENVIRONMENT.get_or_init(|| {
+        let server = MockEpicServer::start();
+
+        let root = std::env::temp_dir().join(format!("alt-manager-tests-{}", std::process::id()));
+        let game_user_settings_folder = root
+            .join("EpicGamesLauncher")
+            .join("Saved")
+            .join("Config")
+            .join("Windows");
+        std::fs::create_dir_all(&game_user_settings_folder).unwrap();
+        std::fs::write(
+            game_user_settings_folder.join("GameUserSettings.ini"),
+            "[RememberMe]\nEnable=True\nData=\n",
+        )
+        .unwrap();
+
+        std::env::set_var(endpoints::BASE_URL_ENV_VAR, &server.base_url);
+        std::env::set_var("PROGRAMDATA", &root);
+        std::env::set_var("localappdata", &root);
+        endpoints::set_endpoints(endpoints::EpicEndpoints::default());
+        //keeps the retry tests fast
+        crate::http::set_settings(crate::http::HttpSettings {
+            backoff_milliseconds: 10,
+            ..crate::http::HttpSettings::default()
+        });
+
+        TestEnvironment { server }
+    })
+}
+
+pub fn launcher_client() -> AuthClient<'static> {
+    get_client!("launcherAppClient2")
+}
+
+pub fn mock_account(display_name: &str) -> MockAccount {
+    environment().server.add_account(display_name)
+}
+
+//a launcher session of a new mock account
+pub async fn login_mock_account(display_name: &str) -> EpicAccount {
+    let account = mock_account(display_name);
+    let refresh_token = environment()
+        .server
+        .create_refresh_token(&account.account_id, launcher_client().id);
+
+    epic::token(Token::RefreshToken(&refresh_token), launcher_client())
+        .await
+        .expect("Failed to login with refresh token")
+}
+
+//a folder of its own for every test, grouped by the file using it
+pub fn test_folder(group: &str, name: &str) -> PathBuf {
+    let folder = std::env::temp_dir()
+        .join(format!("alt-manager-{}-{}", group, std::process::id()))
+        .join(name);
+    std::fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+//copies the fixture so every test owns its user.json
+pub fn copy_legendary_fixture(folder: &Path) {
+    std::fs::create_dir_all(folder).unwrap();
+
+    std::fs::copy(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/fixtures/legendary/user.json"),
+        folder.join("user.json"),
+    )
+    .unwrap();
+}
+
+pub fn launcher_configuration(launcher: Launchers, folder: &Path) -> Configuration {
+    let mut configuration = Configuration::default();
+
+    match launcher {
+        Launchers::Heroic => configuration.heroic_path = folder.display().to_string(),
+        _ => configuration.legendary_path = folder.display().to_string(),
+    }
+
+    configuration.launcher = launcher;
+    configuration
+}
@@ -0,0 +1,47 @@
+use crate::process::{self, matches_name, ProcessError};
+
+#[test]
+fn names_match_across_platforms() {
+    assert!(matches_name("EpicGamesLauncher.exe", "EpicGamesLauncher.exe"));
+    assert!(matches_name("epicgameslauncher.EXE", "EpicGamesLauncher.exe"));
+    assert!(matches_name(
+        "C:\\Program Files (x86)\\Epic Games\\Launcher\\Portal\\Binaries\\Win64\\EpicGamesLauncher.exe",
+        "EpicGamesLauncher.exe"
+    ));
+    assert!(matches_name("/opt/Heroic/heroic", "heroic"));
+    assert!(matches_name("legendary.exe", "legendary"));
+
+    //the old lookup matched any name containing the one searched
+    assert!(!matches_name("EpicGamesLauncherHelper.exe", "EpicGamesLauncher.exe"));
+    assert!(!matches_name("heroic", ""));
+}
+
+#[test]
+fn missing_processes_are_reported() {
+    let name = format!("alt-manager-missing-{}", std::process::id());
+
+    assert_eq!(process::find_processes(&name), Err(ProcessError::NotFound(name.clone())));
+    assert!(!process::is_running(&name));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn terminated_processes_are_waited_for() {
+    use std::time::Duration;
+
+    let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+    let pid = child.id();
+
+    assert!(process::is_alive(pid));
+    assert_eq!(
+        process::wait_for_exit(pid, Duration::from_millis(200)),
+        Err(ProcessError::Timeout(pid))
+    );
+
+    //the child stays a zombie until it is reaped, it must already count as exited
+    process::terminate(pid, false).unwrap();
+    process::wait_for_exit(pid, Duration::from_secs(5)).unwrap();
+    assert!(!process::is_alive(pid));
+
+    let _ = child.wait();
+}
@@ -0,0 +1,96 @@
+use crate::secret_store::{self, FileVault, SecretStore, SecretStoreError, SecretStoreKind};
+
+use super::{environment, test_folder};
+
+fn vault_path(name: &str) -> std::path::PathBuf {
+    let path = test_folder("vaults", "").join(name);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn vault_round_trip() {
+    let vault = FileVault::open_or_create(&vault_path("round_trip.json"), b"passphrase").unwrap();
+
+    let sealed = vault.seal(b"device auth secret").unwrap();
+    assert_ne!(sealed, b"device auth secret".to_vec());
+    assert_eq!(vault.unseal(&sealed).unwrap(), b"device auth secret".to_vec());
+}
+
+#[test]
+fn vault_rejects_wrong_passphrase() {
+    let path = vault_path("wrong_passphrase.json");
+    let sealed = FileVault::open_or_create(&path, b"passphrase")
+        .unwrap()
+        .seal(b"secret")
+        .unwrap();
+
+    let result = FileVault::open_or_create(&path, b"not the passphrase");
+    assert_eq!(result.err(), Some(SecretStoreError::InvalidPassphrase));
+
+    let vault = FileVault::open_or_create(&path, b"passphrase").unwrap();
+    assert_eq!(vault.unseal(&sealed).unwrap(), b"secret".to_vec());
+}
+
+#[test]
+fn locked_vault_cannot_unseal() {
+    let vault = FileVault::open_or_create(&vault_path("locked.json"), b"passphrase").unwrap();
+    let sealed = vault.seal(b"secret").unwrap();
+
+    vault.lock();
+
+    assert!(vault.is_locked());
+    assert_eq!(vault.unseal(&sealed), Err(SecretStoreError::Locked));
+}
+
+//the active store is shared with the tests running in parallel, it is never switched here
+#[test]
+fn sealed_blobs_are_tagged_with_their_store() {
+    environment();
+
+    let sealed = secret_store::seal_in(SecretStoreKind::Vault, b"secret").unwrap();
+
+    assert!(sealed.starts_with("vault:"));
+    assert!(!secret_store::needs_migration_to(&sealed, SecretStoreKind::Vault));
+    assert!(secret_store::needs_migration_to(&sealed, SecretStoreKind::MasterPassword));
+    assert_eq!(secret_store::unseal(&sealed).unwrap().to_vec(), b"secret".to_vec());
+}
+
+#[test]
+fn legacy_dpapi_blobs_need_migration() {
+    //base64 DPAPI output written by 0.1.2 and older
+    assert!(secret_store::needs_migration_to("AQAAANCMnd8BFdERjHoAwE/Cl+sBAAAA", SecretStoreKind::Vault));
+    assert!(secret_store::needs_migration_to("dpapi:AQAAANCMnd8BFdERjHoAwE/Cl+sBAAAA", SecretStoreKind::Vault));
+}
+
+#[test]
+fn master_password_vault_reopens_locked() {
+    let path = vault_path("master.json");
+    let sealed = FileVault::create(&path, b"master password", SecretStoreKind::MasterPassword)
+        .unwrap()
+        .seal(b"secret")
+        .unwrap();
+
+    let vault = FileVault::open(&path, SecretStoreKind::MasterPassword).unwrap();
+    assert!(vault.is_locked());
+    assert_eq!(vault.unseal(&sealed), Err(SecretStoreError::Locked));
+
+    assert_eq!(vault.unlock(b"wrong password"), Err(SecretStoreError::InvalidPassphrase));
+    vault.unlock(b"master password").unwrap();
+    assert_eq!(vault.unseal(&sealed).unwrap(), b"secret".to_vec());
+}
+
+#[test]
+fn private_files_are_never_overwritten() {
+    let path = vault_path("export.json");
+
+    secret_store::write_private_file(&path, b"secrets").unwrap();
+    assert!(secret_store::write_private_file(&path, b"other secrets").is_err());
+    assert_eq!(std::fs::read(&path).unwrap(), b"secrets".to_vec());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+}
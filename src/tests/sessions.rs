@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::{
+    accounts::AccountService,
+    config::Configuration,
+    epic::{self, error_codes, Token},
+    health::AccountHealth,
+    launchers::Launchers,
+};
+
+use super::{
+    environment, launcher_client, launcher_configuration, login_mock_account, mock_account, test_folder,
+    workflows::add_mock_account,
+};
+
+#[tokio::test]
+async fn rejected_access_tokens_are_refreshed() {
+    let account = login_mock_account("ExpiredSessionUser").await;
+    let account_id = account.account_id.clone().unwrap();
+    let first_access_token = account.access_token();
+    let clone = account.clone();
+
+    environment().server.expire_access_tokens(&account_id);
+
+    let details = account.get_infos().await.expect("Failed to get account infos");
+
+    assert_eq!(details.id, account_id);
+    assert_ne!(account.access_token(), first_access_token);
+    assert_eq!(clone.access_token(), account.access_token());
+}
+
+#[tokio::test]
+async fn sessions_about_to_expire_are_refreshed_first() {
+    let mock = mock_account("ShortSessionUser");
+    environment().server.set_session_lifetime(&mock.account_id, 60);
+    let refresh_token = environment()
+        .server
+        .create_refresh_token(&mock.account_id, launcher_client().id);
+    let account = epic::token(Token::RefreshToken(&refresh_token), launcher_client())
+        .await
+        .expect("Failed to login with refresh token");
+    let first_access_token = account.access_token();
+
+    account.get_infos().await.expect("Failed to get account infos");
+
+    //the first token was still accepted, only the expiry could have replaced it
+    assert_ne!(account.access_token(), first_access_token);
+    assert_ne!(account.refresh_token().as_deref(), Some(refresh_token.as_str()));
+}
+
+#[tokio::test]
+async fn device_auth_sessions_log_in_again() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "DeviceAuthSessionUser").await;
+    let mut device_auth = descriptor.device_auth.clone().unwrap();
+    let account = device_auth.login().await.expect("Failed to login with device auth");
+
+    environment().server.expire_access_tokens(&device_auth.account_id);
+    environment().server.revoke_refresh_tokens(&device_auth.account_id);
+
+    let details = account.get_infos().await.expect("Failed to get account infos");
+
+    assert_eq!(details.display_name, "DeviceAuthSessionUser");
+}
+
+#[tokio::test]
+async fn sessions_without_device_auth_report_the_refresh_error() {
+    let account = login_mock_account("RevokedSessionUser").await;
+    let account_id = account.account_id.clone().unwrap();
+
+    environment().server.expire_access_tokens(&account_id);
+    environment().server.revoke_refresh_tokens(&account_id);
+
+    let error = account.get_infos().await.unwrap_err();
+
+    assert_eq!(error.error_code(), Some(error_codes::INVALID_GRANT));
+}
+
+#[tokio::test]
+async fn killed_sessions_stop_working() {
+    let account = login_mock_account("KilledSessionUser").await;
+    let account_id = account.account_id.clone().unwrap();
+    let live_sessions = environment().server.live_sessions(&account_id);
+
+    account.kill_session().await.expect("Failed to kill the session");
+
+    assert_eq!(environment().server.live_sessions(&account_id), live_sessions - 1);
+    assert!(account.get_infos().await.is_err());
+}
+
+#[tokio::test]
+async fn workflows_kill_the_sessions_they_create() {
+    let mut configuration = Configuration::default();
+    let from = add_mock_account(&mut configuration, "SessionCloneFrom").await;
+    let to = add_mock_account(&mut configuration, "SessionCloneTo").await;
+    let from_id = from.account_id().unwrap().to_string();
+    let to_id = to.account_id().unwrap().to_string();
+    environment()
+        .server
+        .set_file(&from_id, "ClientSettings.Sav", b"settings".to_vec());
+
+    //add_mock_account leaves the launcher session it logged in with, like the launcher would
+    let live_sessions = |account_id: &str| environment().server.live_sessions(account_id);
+    let (from_sessions, to_sessions) = (live_sessions(&from_id), live_sessions(&to_id));
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    account_service.clone_settings(&from_id, &to_id).await.unwrap();
+    account_service.check_health().await.unwrap();
+    account_service.refresh_profiles().await.unwrap();
+
+    assert_eq!(live_sessions(&from_id), from_sessions);
+    assert_eq!(live_sessions(&to_id), to_sessions);
+}
+
+#[tokio::test]
+async fn failed_swaps_kill_the_launcher_session() {
+    //legendary can't create its folder where a file is
+    let path = test_folder("sessions", "failed_swap").join("user.json");
+    std::fs::write(&path, b"").unwrap();
+
+    let mut configuration = launcher_configuration(Launchers::Legendary, &path);
+    let descriptor = add_mock_account(&mut configuration, "FailedSwapUser").await;
+    let account_id = descriptor.account_id().unwrap().to_string();
+    let live_sessions = environment().server.live_sessions(&account_id);
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    assert!(account_service.swap_account(&account_id).await.is_err());
+
+    assert_eq!(environment().server.live_sessions(&account_id), live_sessions);
+}
+
+#[tokio::test]
+async fn log_out_everywhere_kills_every_session() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "LogOutEverywhereUser").await;
+    let account_id = descriptor.account_id().unwrap().to_string();
+    environment().server.create_refresh_token(&account_id, launcher_client().id);
+    assert!(environment().server.live_sessions(&account_id) > 0);
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    account_service.log_out_everywhere(&account_id).await.unwrap();
+
+    assert_eq!(environment().server.live_sessions(&account_id), 0);
+    //the device auth is not a session
+    account_service.check_health().await.unwrap();
+    assert_eq!(
+        account_service.configuration().lock().await.accounts[0].health,
+        AccountHealth::Healthy
+    );
+}
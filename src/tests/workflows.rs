@@ -0,0 +1,492 @@
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::{
+    accounts::{self, AccountEvent, AccountLabel, AccountOutcome, AccountService},
+    config::{AddAccountProvider, Configuration, SupersededDeviceAuth},
+    egl::{self, RememberMeEntry},
+    epic::{self, AccountDescriptor, Token},
+    get_client,
+    health::AccountHealth,
+    launchers::Launchers,
+};
+
+use super::{environment, launcher_client, mock_account, GAME_USER_SETTINGS_LOCK};
+
+pub async fn add_mock_account(configuration: &mut Configuration, display_name: &str) -> AccountDescriptor {
+    let account = mock_account(display_name);
+    let refresh_token = environment()
+        .server
+        .create_refresh_token(&account.account_id, launcher_client().id);
+
+    let epic_account = epic::token(Token::RefreshToken(&refresh_token), launcher_client())
+        .await
+        .expect("Failed to login with refresh token");
+
+    configuration
+        .add_account(AddAccountProvider::EpicAccount(&epic_account))
+        .await
+        .expect("Failed to add account")
+        .0
+}
+
+#[tokio::test]
+async fn add_account_creates_a_working_device_auth() {
+    let mut configuration = Configuration::default();
+
+    let descriptor = add_mock_account(&mut configuration, "AddAccountUser").await;
+    let device_auth = descriptor.device_auth.clone().unwrap();
+
+    let account = environment().server.account(&device_auth.account_id).unwrap();
+    assert_eq!(account.display_name, "AddAccountUser");
+    assert_eq!(account.device_auths.len(), 1);
+    assert_ne!(account.device_auths[0].secret, device_auth.secret);
+
+    assert_eq!(configuration.accounts, vec![descriptor.clone()]);
+
+    let mut device_auth = device_auth;
+    let logged_in = device_auth.login().await.expect("Failed to login with device auth");
+    assert_eq!(logged_in.account_id.as_deref(), Some(account.account_id.as_str()));
+    assert_eq!(logged_in.client_id, get_client!("fortniteIOSGameClient").id);
+}
+
+#[tokio::test]
+async fn add_account_from_remember_me_entry() {
+    let mut configuration = Configuration::default();
+    let account = mock_account("RememberMeUser");
+    let refresh_token = environment()
+        .server
+        .create_refresh_token(&account.account_id, launcher_client().id);
+
+    let entry = RememberMeEntry {
+        region: "Prod".to_string(),
+        display_name: account.display_name.clone(),
+        token: refresh_token,
+        ..RememberMeEntry::default()
+    };
+
+    let (descriptor, superseded) = configuration
+        .add_account(AddAccountProvider::RememberMeEntry(&entry))
+        .await
+        .expect("Failed to add account");
+
+    assert_eq!(descriptor.display_name, "RememberMeUser");
+    assert_eq!(superseded, None);
+    assert_eq!(
+        environment().server.account(&account.account_id).unwrap().device_auths.len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn add_account_with_invalid_refresh_token_fails() {
+    let mut configuration = Configuration::default();
+
+    let entry = RememberMeEntry {
+        region: "Prod".to_string(),
+        display_name: "Nobody".to_string(),
+        token: "invalid".to_string(),
+        ..RememberMeEntry::default()
+    };
+
+    let result = configuration
+        .add_account(AddAccountProvider::RememberMeEntry(&entry))
+        .await;
+
+    assert!(result.is_err());
+    assert!(configuration.accounts.is_empty());
+}
+
+#[tokio::test]
+async fn device_code_flow_logs_in_once_approved() {
+    let environment = environment();
+    let account = mock_account("DeviceCodeUser");
+    let client = get_client!("fortniteNewSwitchGameClient");
+
+    let client_token = epic::token(Token::ClientCredentials, client).await.unwrap();
+    assert!(client_token.account_id.is_none());
+
+    let authorization = client_token.get_device_authorization().await.unwrap();
+
+    let pending = epic::token(Token::DeviceCode(&authorization.device_code), client).await;
+    assert!(pending.is_err());
+
+    environment
+        .server
+        .approve_device_code(&authorization.device_code, &account.account_id);
+
+    let logged_in = epic::token(Token::DeviceCode(&authorization.device_code), client)
+        .await
+        .unwrap();
+    assert_eq!(logged_in.display_name.as_deref(), Some("DeviceCodeUser"));
+}
+
+#[tokio::test]
+async fn authorization_code_can_only_be_used_once() {
+    let account = mock_account("AuthorizationCodeUser");
+    let code = environment().server.create_authorization_code(&account.account_id);
+
+    let logged_in = epic::token(Token::AuthorizationCode(&code), launcher_client())
+        .await
+        .unwrap();
+    assert_eq!(logged_in.account_id.as_deref(), Some(account.account_id.as_str()));
+
+    let reused = epic::token(Token::AuthorizationCode(&code), launcher_client()).await;
+    assert!(reused.is_err());
+}
+
+#[tokio::test]
+async fn accounts_can_be_added_with_any_code() {
+    let account_service = AccountService::new(Arc::new(Mutex::new(Configuration::default())));
+
+    let account = mock_account("AddWithRefreshTokenUser");
+    let refresh_token = environment()
+        .server
+        .create_refresh_token(&account.account_id, launcher_client().id);
+    let outcome = account_service
+        .add_account(Token::RefreshToken(&refresh_token), launcher_client())
+        .await
+        .unwrap();
+    assert!(matches!(outcome, AccountOutcome::Added(_)));
+
+    let account = mock_account("AddWithAuthorizationCodeUser");
+    let code = environment().server.create_authorization_code(&account.account_id);
+    let outcome = account_service
+        .add_account(Token::AuthorizationCode(&code), launcher_client())
+        .await
+        .unwrap();
+    assert!(matches!(outcome, AccountOutcome::Added(_)));
+
+    let session = super::login_mock_account("AddWithExchangeCodeUser").await;
+    let exchange_code = session.exchange_code().await.unwrap();
+    let outcome = account_service
+        .add_account(Token::ExchangeCode(&exchange_code), get_client!("fortniteAndroidGameClient"))
+        .await
+        .unwrap();
+    assert!(matches!(outcome, AccountOutcome::Added(_)));
+
+    let display_names: Vec<String> = account_service
+        .configuration()
+        .lock()
+        .await
+        .accounts
+        .iter()
+        .map(|x| x.display_name.clone())
+        .collect();
+    assert_eq!(
+        display_names,
+        vec!["AddWithRefreshTokenUser", "AddWithAuthorizationCodeUser", "AddWithExchangeCodeUser"]
+    );
+}
+
+#[tokio::test]
+async fn swap_account_writes_a_launcher_session() {
+    let _lock = GAME_USER_SETTINGS_LOCK.lock().await;
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "SwapUser").await;
+    let account_id = descriptor.device_auth.as_ref().unwrap().account_id.clone();
+
+    let (event_sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration))).with_events(event_sender);
+
+    let outcome = account_service
+        .swap_account(&account_id)
+        .await
+        .expect("Failed to swap account");
+    assert!(matches!(outcome, AccountOutcome::Swapped(account, Launchers::EpicGamesLauncher) if account.display_name == "SwapUser"));
+
+    let entry = egl::epic_get_remember_me_data().expect("Failed to read RememberMe data");
+    assert_eq!(entry.display_name, "SwapUser");
+    assert_eq!(entry.email, "swapuser@alt-manager.test");
+    assert!(environment().server.is_refresh_token_valid(&entry.token, &account_id));
+
+    let mut current_account = None;
+    while let Ok(event) = events.try_recv() {
+        if let AccountEvent::CurrentAccountChanged(account) = event {
+            current_account = account;
+        }
+    }
+    assert_eq!(current_account.as_deref(), Some("SwapUser"));
+}
+
+#[tokio::test]
+async fn swap_unknown_account_fails() {
+    environment();
+    let account_service = AccountService::new(Arc::new(Mutex::new(Configuration::default())));
+
+    let result = account_service.swap_account("Unknown").await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn link_egl_account_adds_the_current_launcher_account() {
+    let _lock = GAME_USER_SETTINGS_LOCK.lock().await;
+    environment();
+    let account = mock_account("LinkedUser");
+    let refresh_token = environment()
+        .server
+        .create_refresh_token(&account.account_id, launcher_client().id);
+
+    egl::epic_set_remember_me_data(RememberMeEntry {
+        region: "Prod".to_string(),
+        display_name: account.display_name.clone(),
+        token: refresh_token,
+        ..RememberMeEntry::default()
+    })
+    .unwrap();
+
+    let (event_sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let account_service = AccountService::new(Arc::new(Mutex::new(Configuration::default()))).with_events(event_sender);
+
+    let outcome = account_service
+        .link_current_account()
+        .await
+        .expect("Failed to link EGL account");
+    assert!(matches!(outcome, AccountOutcome::Linked(account, Launchers::EpicGamesLauncher) if account.display_name == "LinkedUser"));
+
+    let configuration_mtx = account_service.configuration();
+    let configuration = configuration_mtx.lock().await;
+    assert_eq!(configuration.accounts.len(), 1);
+    assert_eq!(configuration.accounts[0].display_name, "LinkedUser");
+
+    let accounts = std::iter::from_fn(|| events.try_recv().ok()).find_map(|event| match event {
+        AccountEvent::AccountsChanged(accounts) => Some(accounts),
+        _ => None,
+    });
+    assert_eq!(
+        accounts,
+        Some(vec![AccountLabel {
+            account_id: account.account_id.clone(),
+            display_name: "LinkedUser".to_string(),
+            health: AccountHealth::Healthy,
+        }])
+    );
+}
+
+#[tokio::test]
+async fn renamed_accounts_are_edited_in_place() {
+    let mut configuration = Configuration::default();
+    let mut descriptor = add_mock_account(&mut configuration, "BeforeRename").await;
+    descriptor.launch_args = String::new();
+    descriptor.display_name = "AfterRename".to_string();
+
+    configuration.accounts[0].launch_args = "-nosound".to_string();
+    configuration.insert_or_edit(&descriptor);
+
+    assert_eq!(configuration.accounts.len(), 1);
+    assert_eq!(configuration.accounts[0].display_name, "AfterRename");
+    assert_eq!(configuration.accounts[0].launch_args, "-nosound");
+}
+
+#[tokio::test]
+async fn duplicated_accounts_are_merged() {
+    let mut configuration = Configuration::default();
+    let first = add_mock_account(&mut configuration, "DuplicateUser").await;
+    let other = add_mock_account(&mut configuration, "OtherUser").await;
+
+    //what older versions saved after a rename
+    let mut renamed = first.clone();
+    renamed.display_name = "RenamedDuplicateUser".to_string();
+    configuration.accounts.push(renamed);
+    configuration.accounts.push(AccountDescriptor {
+        display_name: "NoDeviceAuth".to_string(),
+        device_auth: None,
+        launch_args: String::new(),
+        email: String::new(),
+        last_verified: None,
+        health: AccountHealth::Unknown,
+    });
+
+    let removed = configuration.remove_accounts_without_device_auth();
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].display_name, "NoDeviceAuth");
+
+    //the renamed entry shares the device auth of the first one
+    configuration.deduplicate_accounts();
+    assert!(configuration.superseded_device_auths.is_empty());
+    assert_eq!(configuration.accounts.len(), 2);
+    assert_eq!(configuration.accounts[0].account_id(), first.account_id());
+    assert_eq!(configuration.accounts[0].display_name, "RenamedDuplicateUser");
+    assert_eq!(configuration.accounts[1], other);
+}
+
+#[tokio::test]
+async fn merged_accounts_keep_their_replaced_device_auth() {
+    let mut configuration = Configuration::default();
+    let first = add_mock_account(&mut configuration, "ReplacedDeviceAuthUser").await;
+
+    let mut relinked = first.clone();
+    relinked.device_auth.as_mut().unwrap().device_id = "relinked".to_string();
+    configuration.accounts.push(relinked);
+
+    configuration.deduplicate_accounts();
+
+    assert_eq!(configuration.accounts.len(), 1);
+    assert_eq!(configuration.accounts[0].device_auth.as_ref().unwrap().device_id, "relinked");
+    assert_eq!(
+        configuration.superseded_device_auths,
+        vec![SupersededDeviceAuth {
+            account_id: first.account_id().unwrap().to_string(),
+            device_id: first.device_auth.as_ref().unwrap().device_id.clone(),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn refresh_profiles_picks_up_renames() {
+    let mut configuration = Configuration::default();
+    let renamed = add_mock_account(&mut configuration, "BeforeRefresh").await;
+    let unchanged = add_mock_account(&mut configuration, "UnchangedRefresh").await;
+    let renamed_id = renamed.account_id().unwrap().to_string();
+    configuration.accounts[0].last_verified = None;
+
+    environment().server.rename_account(&renamed_id, "AfterRefresh");
+
+    let (event_sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration))).with_events(event_sender);
+
+    let outcome = account_service.refresh_profiles().await.unwrap();
+    assert!(matches!(outcome, AccountOutcome::ProfilesRefreshed { changed: 2, failed: 0 }));
+
+    let configuration_mtx = account_service.configuration();
+    let configuration = configuration_mtx.lock().await;
+    assert_eq!(configuration.accounts[0].display_name, "AfterRefresh");
+    assert_eq!(configuration.accounts[0].email, "beforerefresh@alt-manager.test");
+    assert!(configuration.accounts[0].last_verified.is_some());
+    assert_eq!(configuration.accounts[1].display_name, unchanged.display_name);
+
+    let accounts = std::iter::from_fn(|| events.try_recv().ok()).find_map(|event| match event {
+        AccountEvent::AccountsChanged(accounts) => Some(accounts),
+        _ => None,
+    });
+    assert_eq!(accounts.unwrap()[0].display_name, "AfterRefresh");
+}
+
+#[tokio::test]
+async fn health_check_classifies_broken_accounts() {
+    let mut configuration = Configuration::default();
+    let healthy = add_mock_account(&mut configuration, "HealthyUser").await;
+    let revoked = add_mock_account(&mut configuration, "RevokedUser").await;
+    let banned = add_mock_account(&mut configuration, "BannedUser").await;
+    add_mock_account(&mut configuration, "CorruptedUser").await;
+
+    environment().server.revoke_device_auths(revoked.account_id().unwrap());
+    environment().server.ban_account(banned.account_id().unwrap());
+    configuration.accounts[3].device_auth.as_mut().unwrap().secret = "corrupted".to_string();
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    let outcome = account_service.check_health().await.unwrap();
+    assert!(matches!(outcome, AccountOutcome::HealthChecked { healthy: 1, broken: 3, unreachable: 0 }));
+
+    let configuration_mtx = account_service.configuration();
+    let configuration = configuration_mtx.lock().await;
+    let health: Vec<AccountHealth> = configuration.accounts.iter().map(|x| x.health).collect();
+    assert_eq!(
+        health,
+        vec![
+            AccountHealth::Healthy,
+            AccountHealth::Revoked,
+            AccountHealth::Banned,
+            AccountHealth::CipherFailure,
+        ]
+    );
+    assert_eq!(configuration.accounts[0], healthy);
+}
+
+#[tokio::test]
+async fn supported_corrective_actions_are_completed() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "PrivacyPolicyUser").await;
+    let account_id = descriptor.account_id().unwrap().to_string();
+
+    environment()
+        .server
+        .require_corrective_action(&account_id, "PRIVACY_POLICY_ACCEPTANCE");
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    account_service.check_health().await.unwrap();
+    assert_eq!(
+        account_service.configuration().lock().await.accounts[0].health,
+        AccountHealth::ActionRequired
+    );
+
+    let outcome = account_service.complete_corrective_action(&account_id).await.unwrap();
+    assert!(matches!(outcome, AccountOutcome::CorrectiveActionCompleted(account) if account.health == AccountHealth::Healthy));
+    assert_eq!(environment().server.account(&account_id).unwrap().corrective_action, None);
+}
+
+#[tokio::test]
+async fn unsupported_corrective_actions_are_reported() {
+    let mut configuration = Configuration::default();
+    let descriptor = add_mock_account(&mut configuration, "DateOfBirthUser").await;
+    let account_id = descriptor.account_id().unwrap().to_string();
+
+    environment().server.require_corrective_action(&account_id, "DATE_OF_BIRTH");
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    let error = account_service.complete_corrective_action(&account_id).await.unwrap_err();
+
+    assert!(error.to_string().contains("entering a date of birth"));
+    assert!(error.to_string().contains("epicgames.com"));
+}
+
+#[tokio::test]
+async fn accounts_are_resolved_by_id_or_unique_display_name() {
+    let mut configuration = Configuration::default();
+    let first = add_mock_account(&mut configuration, "ResolveUser").await;
+    let second = add_mock_account(&mut configuration, "ResolveOther").await;
+    let first_id = first.account_id().unwrap();
+
+    assert_eq!(accounts::resolve_account(&configuration, first_id).unwrap(), first_id);
+    assert_eq!(accounts::resolve_account(&configuration, "ResolveUser").unwrap(), first_id);
+    assert!(accounts::resolve_account(&configuration, "Unknown").is_err());
+
+    //two accounts can share a display name, only their id tells them apart
+    let mut homonym = second.clone();
+    homonym.display_name = "ResolveUser".to_string();
+    configuration.insert_or_edit(&homonym);
+
+    assert!(accounts::resolve_account(&configuration, "ResolveUser").is_err());
+    assert_eq!(accounts::resolve_account(&configuration, first_id).unwrap(), first_id);
+}
+
+#[tokio::test]
+async fn clone_settings_copies_client_settings() {
+    let mut configuration = Configuration::default();
+    let from = add_mock_account(&mut configuration, "CloneFrom").await;
+    let to = add_mock_account(&mut configuration, "CloneTo").await;
+    let from_id = from.device_auth.as_ref().unwrap().account_id.clone();
+    let to_id = to.device_auth.as_ref().unwrap().account_id.clone();
+
+    let settings = b"mock client settings".to_vec();
+    environment()
+        .server
+        .set_file(&from_id, "ClientSettings.Sav", settings.clone());
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    account_service
+        .clone_settings(&from_id, &to_id)
+        .await
+        .expect("Failed to clone settings");
+
+    let account = environment().server.account(&to_id).unwrap();
+    assert_eq!(account.files.get("ClientSettings.Sav"), Some(&settings));
+    assert!(account.eula_accepted);
+    assert!(account.fortnite_access);
+}
+
+#[tokio::test]
+async fn clone_settings_without_source_file_fails() {
+    let mut configuration = Configuration::default();
+    let from = add_mock_account(&mut configuration, "EmptyFrom").await;
+    let to = add_mock_account(&mut configuration, "EmptyTo").await;
+
+    let account_service = AccountService::new(Arc::new(Mutex::new(configuration)));
+    let result = account_service
+        .clone_settings(from.account_id().unwrap(), to.account_id().unwrap())
+        .await;
+
+    assert!(result.is_err());
+}